@@ -2,23 +2,79 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::{Error, Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::sync::{Arc, RwLock};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 use log::{debug, error, info};
-use request::Request;
+use request::{
+    ExtractError, ForwardingHeaders, Header, Limits, ParseError, Parsed, Request, RequestParser,
+    TrustedProxies,
+};
 use response::{BaseResponse, Response};
+use router::{Match, Router};
 use thread_pool::ThreadPool;
 
 use methods::Methods;
 
 mod utils;
 
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+const CONTINUE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\n";
+
 type RouteFn = Box<dyn Fn(Request) -> Response + Send + Sync + 'static>;
+type ExpectFn = Box<dyn Fn(&Header) -> bool + Send + Sync + 'static>;
+
+pub trait IntoResponse {
+    fn into_response(self) -> Response;
+}
+
+impl IntoResponse for Response {
+    fn into_response(self) -> Response {
+        self
+    }
+}
+
+impl IntoResponse for ExtractError {
+    fn into_response(self) -> Response {
+        BaseResponse::client_error().bad_request_with(&self.to_string())
+    }
+}
+
+impl<E: IntoResponse> IntoResponse for Result<Response, E> {
+    fn into_response(self) -> Response {
+        self.unwrap_or_else(IntoResponse::into_response)
+    }
+}
+
+pub struct Route {
+    handler: RouteFn,
+    body_limit: Option<usize>,
+    expect: Option<ExpectFn>,
+    streaming: bool,
+}
+
+impl Route {
+    fn new<F, R>(f: F, streaming: bool) -> Self
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        Self {
+            handler: Box::new(move |request| f(request).into_response()),
+            body_limit: None,
+            expect: None,
+            streaming,
+        }
+    }
+}
 
 pub struct Server {
     address: String,
     listener: TcpListener,
     pool: ThreadPool,
+    limits: Limits,
+    trusted: TrustedProxies,
     pub api: Api,
 }
 
@@ -30,10 +86,32 @@ impl Server {
             address: addr.to_string(),
             listener: TcpListener::bind(addr).unwrap(),
             pool: ThreadPool::new(thread_num),
+            limits: Limits::default(),
+            trusted: TrustedProxies::new(),
             api: Api::new(),
         }
     }
 
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Believes the forwarding headers sent by the given proxies, addresses
+    /// or CIDR networks. Which headers those are is set with
+    /// `forwarding_headers`, `X-Forwarded-For` alone by default.
+    pub fn trust_proxies(&mut self, proxies: &[&str]) {
+        for proxy in proxies {
+            match self.trusted.add(proxy) {
+                true => debug!("Trust proxy {}", proxy),
+                false => error!("{} is not a valid proxy address", proxy),
+            }
+        }
+    }
+
+    pub fn forwarding_headers(&mut self, headers: ForwardingHeaders) {
+        self.trusted.set_headers(headers);
+    }
+
     pub fn run(&self) {
         info!("Simple HTTP Server start running");
         info!("Start listening on {}", self.address);
@@ -42,38 +120,53 @@ impl Server {
             let stream = stream.unwrap();
 
             let routes = self.api.routes.clone();
+            let limits = self.limits;
+            let trusted = self.trusted.clone();
 
-            self.pool.execute(|| {
-                Self::handle_connection(stream, routes);
+            self.pool.execute(move || {
+                Self::handle_connection(stream, routes, limits, trusted);
             });
         }
     }
 
+    /// Serves the files under `static_dir_path` below `mount_point`, a
+    /// directory is answered with its `index.html`.
     pub fn mount(&mut self, static_dir_path: &str, mount_point: &str) {
-        let path = static_dir_path.to_string();
+        let path = PathBuf::from(static_dir_path);
 
-        let result = fs::read_dir(static_dir_path);
+        if let Err(e) = fs::read_dir(&path) {
+            error!("{}", e);
+            return;
+        }
 
-        if result.is_ok() {
-            let mut root = utils::make_root_path(mount_point);
+        let root = utils::make_root_path(mount_point);
 
-            info!(
-                "Mount Static Directory From '{}' To '{}'",
-                static_dir_path, &root
-            );
+        info!(
+            "Mount Static Directory From '{}' To '{}'",
+            static_dir_path, &root
+        );
 
-            self.api.get(&root, move |_| {
-                BaseResponse::success()
-                    .file(&format!("{}/index.html", path))
-                    .unwrap_or_else(|_| BaseResponse::client_error().not_found())
-            });
+        self.api.get(&format!("{}*path", root), move |request| {
+            let relative = Path::new(request.param::<&str>("path").unwrap_or(""));
 
-            root.pop();
+            // parameters are decoded, `%2F..` must not climb out either
+            if !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return BaseResponse::client_error().not_found();
+            }
 
-            self.walk_dir(static_dir_path, mount_point);
-        } else {
-            error!("{}", result.err().unwrap());
-        }
+            let mut file = path.join(relative);
+
+            if file.is_dir() {
+                file.push("index.html");
+            }
+
+            file.to_str()
+                .and_then(|file| BaseResponse::success().file(file).ok())
+                .unwrap_or_else(|| BaseResponse::client_error().not_found())
+        });
     }
 
     pub fn redirect(&mut self, method: Methods, origin: &str, target: &str) {
@@ -84,52 +177,245 @@ impl Server {
         });
     }
 
-    fn walk_dir(&mut self, dir_path: &str, root_path: &str) {
-        let dir = fs::read_dir(dir_path).unwrap();
+    fn handle_connection(
+        mut stream: TcpStream,
+        routes: RouteTable,
+        limits: Limits,
+        trusted: TrustedProxies,
+    ) {
+        let mut parser = RequestParser::with_limits(limits);
+        let mut buffer = [0; 1024];
+        let mut expect_continue = false;
 
-        dir.for_each(|d| {
-            let file_or_dir = d.unwrap();
+        if stream.set_read_timeout(Some(KEEP_ALIVE_TIMEOUT)).is_err() {
+            return;
+        }
 
-            if file_or_dir.file_type().unwrap().is_dir() {
-                self.walk_dir(file_or_dir.path().to_str().unwrap(), root_path);
-            } else {
-                let file_path = file_or_dir.path().to_str().unwrap().to_string();
+        loop {
+            let mut request = match parser.parse() {
+                Ok(Parsed::Complete(request)) => *request,
+                Ok(Parsed::Head) => {
+                    match Self::prepare_body(&mut parser, &routes) {
+                        Ok((expect, true)) => {
+                            Self::stream_request(
+                                &mut stream,
+                                &mut parser,
+                                &routes,
+                                &trusted,
+                                expect,
+                            );
+                            return;
+                        }
+                        Ok((expect, false)) => expect_continue = expect,
+                        Err(response) => {
+                            error!("{}: expectation rejected", response.message());
+                            let _ = Self::send_response(
+                                &mut stream,
+                                response.set_header("Connection", "close"),
+                            );
+                            return;
+                        }
+                    }
+
+                    continue;
+                }
+                Ok(Parsed::Partial) if expect_continue => {
+                    expect_continue = false;
+
+                    if stream.write_all(CONTINUE).is_err() {
+                        return;
+                    }
+
+                    continue;
+                }
+                Ok(Parsed::Partial) => match stream.read(&mut buffer) {
+                    Ok(0) | Err(_) => return,
+                    Ok(length) => {
+                        parser.feed(&buffer[0..length]);
+                        continue;
+                    }
+                },
+                Err(e) => {
+                    let response = Self::parse_error(&e);
+                    error!("{}: {}", response.message(), e);
+                    let _ = Self::send_response(
+                        &mut stream,
+                        response.set_header("Connection", "close"),
+                    );
+                    return;
+                }
+            };
+
+            expect_continue = false;
+
+            Self::identify(&mut request, &stream, &trusted);
+
+            let keep_alive = request.keep_alive();
+            let head = request.request_line.method == Methods::Head;
+
+            let mut response = Self::dispatch(request, &routes);
+
+            if head {
+                response = response.without_body();
+            }
 
-                let mut root = utils::make_root_path(root_path);
+            if !keep_alive {
+                response = response.set_header("Connection", "close");
+            }
 
-                root.push_str(&file_path.split('/').collect::<Vec<&str>>()[1..].join("/"));
+            if Self::send_response(&mut stream, response).is_err() || !keep_alive {
+                return;
+            }
+        }
+    }
 
-                self.api.get(&root, move |_| {
-                    BaseResponse::success()
-                        .file(&file_path)
-                        .unwrap_or_else(|_| BaseResponse::client_error().not_found())
-                });
+    // Applies the per-route settings to the body about to be read, tells
+    // whether the client waits for `100 Continue` before sending it and
+    // whether the route reads the body as a stream.
+    fn prepare_body(
+        parser: &mut RequestParser,
+        routes: &RouteTable,
+    ) -> Result<(bool, bool), Response> {
+        let (request_line, headers) = match parser.head() {
+            None => return Ok((false, false)),
+            Some(head) => head,
+        };
+
+        let table = routes.read();
+        let route =
+            find_route(&table, &request_line.method, &request_line.url).map(|found| found.value);
+
+        let expect = match headers.get("Expect") {
+            None => false,
+            Some(_) if request_line.http_version == "HTTP/1.0" => false,
+            Some(expect) if expect.eq_ignore_ascii_case("100-continue") => {
+                match route.and_then(|route| route.expect.as_ref()) {
+                    Some(accept) if !accept(headers) => {
+                        return Err(BaseResponse::client_error().expectation_failed())
+                    }
+                    _ => true,
+                }
             }
-        });
+            Some(_) => return Err(BaseResponse::client_error().expectation_failed()),
+        };
+
+        let streaming = route.is_some_and(|route| route.streaming);
+
+        if let Some(limit) = route.and_then(|route| route.body_limit) {
+            parser.set_body_limit(limit);
+        }
+
+        Ok((expect, streaming))
     }
 
-    fn handle_connection(mut stream: TcpStream, routes: RouteTable) {
-        let mut buffer = [0; 1024];
+    // The body reader may read ahead of the request, so the connection
+    // is closed once the response is sent.
+    fn stream_request(
+        stream: &mut TcpStream,
+        parser: &mut RequestParser,
+        routes: &RouteTable,
+        trusted: &TrustedProxies,
+        expect_continue: bool,
+    ) {
+        if let Err(e) = parser.check_stream_length() {
+            let response = Self::parse_error(&e);
+            error!("{}: {}", response.message(), e);
+
+            let _ = Self::send_response(stream, response.set_header("Connection", "close"));
+            return;
+        }
 
-        let length = stream.read(&mut buffer).unwrap();
+        if expect_continue && parser.remaining().is_empty() && stream.write_all(CONTINUE).is_err() {
+            return;
+        }
 
-        let request = Request::parse(&buffer[0..length]);
+        let source = match stream.try_clone() {
+            Ok(source) => source,
+            Err(_) => return,
+        };
 
-        let request_method = &request.request_line.method;
-        let request_url = &request.request_line.url;
+        let response = match parser.stream_body(source) {
+            Ok(Some(mut request)) => {
+                Self::identify(&mut request, stream, trusted);
 
-        let response = match routes.get(request_method).read().unwrap().get(request_url) {
-            None => Self::target_not_found(request_method, request_url)(),
-            Some(res) => {
-                let method = request.request_line.method.to_string();
-                let url = request.request_line.url.clone();
-                let response = res(request) as Response;
-                info!("{} {} {}", method, url, response.message());
+                match request.request_line.method == Methods::Head {
+                    true => Self::dispatch(request, routes).without_body(),
+                    false => Self::dispatch(request, routes),
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                let response = Self::parse_error(&e);
+                error!("{}: {}", response.message(), e);
                 response
             }
-        } as Response;
+        };
 
-        Self::send_response(stream, response).unwrap();
+        let _ = Self::send_response(stream, response.set_header("Connection", "close"));
+    }
+
+    fn identify(request: &mut Request, stream: &TcpStream, trusted: &TrustedProxies) {
+        if let Ok(peer_addr) = stream.peer_addr() {
+            request.set_peer_addr(peer_addr, trusted);
+        }
+    }
+
+    fn dispatch(mut request: Request, routes: &RouteTable) -> Response {
+        let request_method = &request.request_line.method.clone();
+        let request_url = &request.request_line.url.clone();
+
+        let table = routes.read();
+
+        match find_route(&table, request_method, request_url) {
+            None if matches!(request_method, Methods::Extension(_))
+                && !table.contains_key(request_method) =>
+            {
+                error!("{} {} 501 NOT IMPLEMENTED", request_method, request_url);
+                BaseResponse::server_error().not_implemented()
+            }
+            None if request_url == "*" && *request_method != Methods::Options => {
+                error!("{} * 400 BAD REQUEST", request_method);
+                BaseResponse::client_error().bad_request()
+            }
+            None => {
+                let allowed = allowed_methods(&table, request_url)
+                    .iter()
+                    .map(Methods::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                match request_method {
+                    _ if allowed.is_empty() => {
+                        Self::target_not_found(request_method, request_url)()
+                    }
+                    Methods::Options => {
+                        info!("OPTIONS {} 204 NO CONTENT", request_url);
+                        BaseResponse::success()
+                            .no_content()
+                            .set_header("Allow", &allowed)
+                    }
+                    _ => {
+                        error!("{} {} 405 METHOD NOT ALLOWED", request_method, request_url);
+                        BaseResponse::client_error().method_not_allowed(&allowed)
+                    }
+                }
+            }
+            Some(found) => {
+                let client = request
+                    .client_ip()
+                    .map_or_else(|| "-".to_string(), |ip| ip.to_string());
+                request.set_params(found.params);
+                let response = (found.value.handler)(request) as Response;
+                info!(
+                    "{} {} {} {}",
+                    client,
+                    request_method,
+                    request_url,
+                    response.message()
+                );
+                response
+            }
+        }
     }
 
     fn target_not_found(
@@ -140,7 +426,25 @@ impl Server {
         Box::new(|| BaseResponse::client_error().not_found())
     }
 
-    fn send_response(mut stream: TcpStream, mut response: Response) -> Result<(), Error> {
+    fn parse_error(error: &ParseError) -> Response {
+        match error {
+            ParseError::UriTooLong => BaseResponse::client_error().uri_too_long(),
+            ParseError::HeadersTooLarge => {
+                BaseResponse::client_error().request_header_fields_too_large()
+            }
+            ParseError::BodyTooLarge => BaseResponse::client_error().payload_too_large(),
+            ParseError::UnsupportedEncoding(_) => {
+                BaseResponse::client_error().unsupported_media_type()
+            }
+            ParseError::UnsupportedTransferCoding(_) => {
+                BaseResponse::server_error().not_implemented()
+            }
+            ParseError::InvalidVersion => BaseResponse::server_error().http_version_not_supported(),
+            _ => BaseResponse::client_error().bad_request(),
+        }
+    }
+
+    fn send_response(stream: &mut TcpStream, mut response: Response) -> Result<(), Error> {
         stream.write_all(&response.as_bytes())?;
         stream.flush()?;
 
@@ -148,33 +452,102 @@ impl Server {
     }
 }
 
+type Routes = HashMap<Methods, Router<Route>>;
+
+// `HEAD` falls back to the `GET` route of the same target, fallback
+// handlers are only used when no route matches.
+fn find_route<'a>(routes: &'a Routes, method: &Methods, target: &str) -> Option<Match<'a, Route>> {
+    let find = |method: &Methods| routes.get(method).and_then(|router| router.get(target));
+    let fallback = |method: &Methods| {
+        routes
+            .get(method)
+            .and_then(Router::fallback)
+            .map(|value| Match {
+                value,
+                params: vec![],
+            })
+    };
+
+    match method {
+        Methods::Head => find(method)
+            .or_else(|| find(&Methods::Get))
+            .or_else(|| fallback(method))
+            .or_else(|| fallback(&Methods::Get)),
+        method => find(method).or_else(|| fallback(method)),
+    }
+}
+
+// The methods a target answers to, `*` stands for the whole server.
+// `HEAD` and `OPTIONS` are answered automatically.
+fn allowed_methods(routes: &Routes, target: &str) -> Vec<Methods> {
+    let mut methods = routes
+        .iter()
+        .filter(|(_, router)| match target {
+            "*" => !router.is_empty() || router.fallback().is_some(),
+            target => router.get(target).is_some() || router.fallback().is_some(),
+        })
+        .map(|(method, _)| method.clone())
+        .collect::<Vec<Methods>>();
+
+    if methods.is_empty() && target != "*" {
+        return methods;
+    }
+
+    if methods.contains(&Methods::Get) && !methods.contains(&Methods::Head) {
+        methods.push(Methods::Head);
+    }
+
+    if !methods.contains(&Methods::Options) {
+        methods.push(Methods::Options);
+    }
+
+    methods.sort_by_key(|method| {
+        let rank = [
+            Methods::Get,
+            Methods::Head,
+            Methods::Post,
+            Methods::Put,
+            Methods::Delete,
+            Methods::Patch,
+            Methods::Options,
+            Methods::Trace,
+            Methods::Connect,
+        ]
+        .iter()
+        .position(|standard| standard == method)
+        .unwrap_or(usize::MAX);
+
+        (rank, method.to_string())
+    });
+
+    methods
+}
+
 #[derive(Default)]
 pub struct RouteTable {
-    get: Arc<RwLock<HashMap<String, RouteFn>>>,
-    post: Arc<RwLock<HashMap<String, RouteFn>>>,
+    routes: Arc<RwLock<Routes>>,
 }
 
 impl RouteTable {
     pub fn new() -> Self {
         Self {
-            get: Arc::new(RwLock::new(HashMap::new())),
-            post: Arc::new(RwLock::new(HashMap::new())),
+            routes: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
-    pub fn get(&self, method: &Methods) -> Arc<RwLock<HashMap<String, RouteFn>>> {
-        match method {
-            Methods::Get => Arc::clone(&self.get),
-            Methods::Post => Arc::clone(&self.post),
-        }
+    pub fn read(&self) -> RwLockReadGuard<'_, Routes> {
+        self.routes.read().unwrap()
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, Routes> {
+        self.routes.write().unwrap()
     }
 }
 
 impl Clone for RouteTable {
     fn clone(&self) -> Self {
         Self {
-            get: Arc::clone(&self.get),
-            post: Arc::clone(&self.post),
+            routes: Arc::clone(&self.routes),
         }
     }
 }
@@ -191,30 +564,354 @@ impl Api {
         }
     }
 
-    pub fn get<F>(&mut self, route: &str, f: F)
+    pub fn get<F, R>(&mut self, route: &str, f: F)
     where
-        F: Fn(Request) -> Response + Send + Sync + 'static,
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
     {
         self.response(Methods::Get, route, f);
     }
 
-    pub fn post<F>(&mut self, route: &str, f: F)
+    pub fn head<F, R>(&mut self, route: &str, f: F)
     where
-        F: Fn(Request) -> Response + Send + Sync + 'static,
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response(Methods::Head, route, f);
+    }
+
+    pub fn post<F, R>(&mut self, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
     {
         self.response(Methods::Post, route, f);
     }
 
-    pub fn response<F>(&mut self, method: Methods, route: &str, f: F)
+    pub fn put<F, R>(&mut self, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response(Methods::Put, route, f);
+    }
+
+    pub fn delete<F, R>(&mut self, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response(Methods::Delete, route, f);
+    }
+
+    pub fn patch<F, R>(&mut self, route: &str, f: F)
     where
-        F: Fn(Request) -> Response + Send + Sync + 'static,
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response(Methods::Patch, route, f);
+    }
+
+    pub fn options<F, R>(&mut self, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.response(Methods::Options, route, f);
+    }
+
+    pub fn response<F, R>(&mut self, method: Methods, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.insert(method, route, f, false);
+    }
+
+    /// Registers a handler reading the request body from the connection
+    /// through `Request::take_reader` instead of having it buffered.
+    pub fn stream<F, R>(&mut self, method: Methods, route: &str, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.insert(method, route, f, true);
+    }
+
+    /// Registers the handler answering `method` requests no route matches.
+    pub fn fallback<F, R>(&mut self, method: Methods, f: F)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.routes
+            .write()
+            .entry(method.clone())
+            .or_default()
+            .set_fallback(Route::new(f, false));
+
+        debug!("{}: Add fallback to Route Table", method);
+    }
+
+    fn insert<F, R>(&mut self, method: Methods, route: &str, f: F, streaming: bool)
+    where
+        F: Fn(Request) -> R + Send + Sync + 'static,
+        R: IntoResponse,
     {
         self.routes
-            .get(&method)
             .write()
-            .unwrap()
-            .insert(route.to_string(), Box::new(f));
+            .entry(method.clone())
+            .or_default()
+            .insert(route, Route::new(f, streaming));
 
         debug!("{}: Add {} to Route Table", method, route);
     }
+
+    pub fn limit(&mut self, method: Methods, route: &str, max_body_size: usize) {
+        match self
+            .routes
+            .write()
+            .get_mut(&method)
+            .and_then(|router| router.get_mut(route))
+        {
+            Some(target) => {
+                target.body_limit = Some(max_body_size);
+                debug!("{}: Limit {} to {} bytes", method, route, max_body_size);
+            }
+            None => error!("{}: {} is not in Route Table", method, route),
+        }
+    }
+
+    pub fn expect<F>(&mut self, method: Methods, route: &str, f: F)
+    where
+        F: Fn(&Header) -> bool + Send + Sync + 'static,
+    {
+        match self
+            .routes
+            .write()
+            .get_mut(&method)
+            .and_then(|router| router.get_mut(route))
+        {
+            Some(target) => {
+                target.expect = Some(Box::new(f));
+                debug!("{}: Add expectation check to {}", method, route);
+            }
+            None => error!("{}: {} is not in Route Table", method, route),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Api, Server};
+    use methods::Methods;
+    use request::{Limits, Request, TrustedProxies};
+    use response::BaseResponse;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    // Serves a single connection on a loopback port with the routes of `api`.
+    fn connect(api: &Api, limits: Limits) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let routes = api.routes.clone();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            Server::handle_connection(stream, routes, limits, TrustedProxies::new());
+        });
+
+        let stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+
+        stream
+    }
+
+    fn read_all(stream: &mut TcpStream) -> String {
+        let mut response = vec![];
+        let _ = stream.read_to_end(&mut response);
+
+        String::from_utf8_lossy(&response).to_string()
+    }
+
+    fn dispatch(api: &Api, method: &str, target: &str) -> String {
+        let data = format!("{} {} HTTP/1.1\r\nHost: localhost\r\n\r\n", method, target);
+        let request = Request::parse(data.as_bytes()).unwrap();
+        let bytes = Server::dispatch(request, &api.routes).as_bytes();
+
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn api() -> Api {
+        let mut api = Api::new();
+
+        api.delete("/items", |_| BaseResponse::success().string("deleted"));
+        api.response(Methods::from("PURGE"), "/items", |_| {
+            BaseResponse::success().string("purged")
+        });
+        api.post("/items", |_| BaseResponse::success().string("posted"));
+        api.get("/items", |_| BaseResponse::success().string("items"));
+
+        api
+    }
+
+    #[test]
+    fn answer_method_not_allowed_with_allow() {
+        let response = dispatch(&api(), "PUT", "/items");
+
+        assert!(response.starts_with("HTTP/1.1 405 "));
+        assert!(response.contains("Allow: GET, HEAD, POST, DELETE, OPTIONS, PURGE\r\n"));
+
+        let response = dispatch(&api(), "HEAD", "/items");
+
+        assert!(response.starts_with("HTTP/1.1 200 "));
+    }
+
+    #[test]
+    fn answer_options() {
+        let response = dispatch(&api(), "OPTIONS", "/items");
+
+        assert!(response.starts_with("HTTP/1.1 204 "));
+        assert!(response.contains("Allow: GET, HEAD, POST, DELETE, OPTIONS, PURGE\r\n"));
+
+        let mut api = Api::new();
+        api.patch("/a", |_| BaseResponse::success().string("a"));
+        api.get("/b", |_| BaseResponse::success().string("b"));
+
+        let response = dispatch(&api, "OPTIONS", "*");
+
+        assert!(response.starts_with("HTTP/1.1 204 "));
+        assert!(response.contains("Allow: GET, HEAD, PATCH, OPTIONS\r\n"));
+        assert!(dispatch(&api, "GET", "*").starts_with("HTTP/1.1 400 "));
+    }
+
+    #[test]
+    fn answer_not_found_and_fallbacks() {
+        assert!(dispatch(&api(), "GET", "/missing").starts_with("HTTP/1.1 404 "));
+        assert!(dispatch(&api(), "OPTIONS", "/missing").starts_with("HTTP/1.1 404 "));
+        assert!(dispatch(&api(), "BREW", "/items").starts_with("HTTP/1.1 501 "));
+
+        let mut api = api();
+        api.fallback(Methods::Post, |_| {
+            BaseResponse::success().string("fallback")
+        });
+
+        let response = dispatch(&api, "GET", "/missing");
+
+        assert!(response.starts_with("HTTP/1.1 405 "));
+        assert!(response.contains("Allow: POST, OPTIONS\r\n"));
+        assert!(dispatch(&api, "POST", "/missing").ends_with("fallback"));
+        assert!(dispatch(&api, "POST", "/items").ends_with("posted"));
+    }
+
+    fn upload_api() -> Api {
+        let mut api = Api::new();
+
+        api.post("/upload", |request| {
+            BaseResponse::success().string(&request.bytes().len().to_string())
+        });
+        api.post("/guarded", |_| BaseResponse::success().string("accepted"));
+        api.stream(Methods::Post, "/stream", |_| {
+            BaseResponse::success().string("streamed")
+        });
+        api.limit(Methods::Post, "/stream", 4);
+        api.expect(Methods::Post, "/guarded", |headers| {
+            headers.get("Authorization").is_some()
+        });
+
+        api
+    }
+
+    #[test]
+    fn continue_once_the_body_is_expected() {
+        let mut stream = connect(&upload_api(), Limits::default());
+        stream
+            .write_all(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nExpect: 100-continue\r\nContent-Length: 3\r\n\r\n")
+            .unwrap();
+
+        let mut interim = [0; 25];
+        stream.read_exact(&mut interim).unwrap();
+
+        assert_eq!(&interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+
+        stream.write_all(b"abc").unwrap();
+
+        let response = read_all(&mut stream);
+
+        assert!(response.starts_with("HTTP/1.1 200 "));
+        assert!(response.ends_with("\r\n3"));
+    }
+
+    #[test]
+    fn reject_expectations() {
+        let cases: [(&[u8], &str); 4] = [
+            (
+                b"POST /upload HTTP/1.1\r\nHost: localhost\r\nExpect: teapot\r\nContent-Length: 3\r\n\r\n",
+                "HTTP/1.1 417 ",
+            ),
+            (
+                b"POST /guarded HTTP/1.1\r\nHost: localhost\r\nExpect: 100-continue\r\nContent-Length: 3\r\n\r\n",
+                "HTTP/1.1 417 ",
+            ),
+            (
+                b"POST /upload HTTP/1.1\r\nHost: localhost\r\nExpect: 100-continue\r\nContent-Length: 5\r\n\r\n",
+                "HTTP/1.1 413 ",
+            ),
+            (
+                b"POST /stream HTTP/1.1\r\nHost: localhost\r\nExpect: 100-continue\r\nContent-Length: 100\r\n\r\n",
+                "HTTP/1.1 413 ",
+            ),
+        ];
+
+        let limits = Limits {
+            max_body_size: 4,
+            ..Limits::default()
+        };
+
+        for (data, status) in cases {
+            let mut stream = connect(&upload_api(), limits);
+            stream.write_all(data).unwrap();
+
+            assert!(read_all(&mut stream).starts_with(status));
+        }
+    }
+
+    #[test]
+    fn skip_continue_when_not_needed() {
+        let cases: [&[u8]; 2] = [
+            b"POST /upload HTTP/1.0\r\nExpect: 100-continue\r\nContent-Length: 3\r\n\r\nabc",
+            b"POST /upload HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nExpect: 100-continue\r\nContent-Length: 3\r\n\r\nabc",
+        ];
+
+        for data in cases {
+            let mut stream = connect(&upload_api(), Limits::default());
+            stream.write_all(data).unwrap();
+
+            let response = read_all(&mut stream);
+
+            assert!(response.starts_with("HTTP/1.1 200 "));
+            assert!(!response.contains("100 Continue"));
+        }
+    }
+
+    #[test]
+    fn answer_head_from_get_route() {
+        let mut api = Api::new();
+        api.get("/hello", |_| BaseResponse::success().string("hello"));
+
+        let mut stream = connect(&api, Limits::default());
+        stream
+            .write_all(b"HEAD /hello HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+
+        let response = read_all(&mut stream);
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 5\r\n"));
+        assert!(response.ends_with("\r\n\r\n"));
+    }
 }
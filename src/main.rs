@@ -1,3 +1,4 @@
+use request::{Authorization, BodyTooLarge, ForwardingHeaders};
 use response::BaseResponse;
 
 use methods::Methods;
@@ -6,16 +7,64 @@ use simple_http_server::Server;
 fn main() {
     let mut server = Server::new("0.0.0.0:7878", 32);
 
+    server.trust_proxies(&["127.0.0.1", "::1"]);
+    server.forwarding_headers(ForwardingHeaders::XForwarded {
+        proto: true,
+        host: false,
+    });
+
     server.mount("dist", "/");
 
     server.api.post("/echo", |request| {
-        let body = request.body.to_string();
+        let body = request.body().to_string();
 
         BaseResponse::success()
             .string(body.as_str())
             .set_content_type("application/json")
     });
 
+    server.api.get("/hello", |request| {
+        match request.negotiate(&["text/html", "application/json"]) {
+            Some("application/json") => BaseResponse::success()
+                .string(r#"{"message": "hello"}"#)
+                .set_content_type("application/json"),
+            _ => BaseResponse::success()
+                .string("<h1>hello</h1>")
+                .set_content_type("text/html"),
+        }
+    });
+
+    server.api.get("/users/{id:u64}", |request| {
+        let id = request.param::<u64>("id").unwrap_or_default();
+
+        BaseResponse::success()
+            .string(&format!("{{\"id\": {}}}", id))
+            .set_content_type("application/json")
+    });
+
+    server
+        .api
+        .get("/private", |request| match request.authorization() {
+            Some(Authorization::Basic { username, password }) if password == "secret" => {
+                BaseResponse::success().string(&format!("hello {}", username))
+            }
+            _ => BaseResponse::client_error().unauthorized(r#"Basic realm="private""#),
+        });
+
+    server.api.stream(Methods::Post, "/upload", |mut request| {
+        let spooled = request.take_reader().map(|mut reader| reader.spool());
+
+        match spooled {
+            Some(Ok(file)) => BaseResponse::success()
+                .string(&format!("{{\"size\": {}}}", file.len().unwrap_or_default()))
+                .set_content_type("application/json"),
+            Some(Err(e)) if BodyTooLarge::caused(&e) => {
+                BaseResponse::client_error().payload_too_large()
+            }
+            _ => BaseResponse::client_error().bad_request(),
+        }
+    });
+
     server.redirect(Methods::Get, "/", "/red");
 
     server.run();
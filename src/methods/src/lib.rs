@@ -1,9 +1,17 @@
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Methods {
     Get,
+    Head,
     Post,
+    Put,
+    Delete,
+    Patch,
+    Options,
+    Trace,
+    Connect,
+    Extension(String),
 }
 
 impl Display for Methods {
@@ -12,19 +20,51 @@ impl Display for Methods {
             Methods::Get => {
                 write!(f, "GET")
             }
+            Methods::Head => {
+                write!(f, "HEAD")
+            }
             Methods::Post => {
                 write!(f, "POST")
             }
+            Methods::Put => {
+                write!(f, "PUT")
+            }
+            Methods::Delete => {
+                write!(f, "DELETE")
+            }
+            Methods::Patch => {
+                write!(f, "PATCH")
+            }
+            Methods::Options => {
+                write!(f, "OPTIONS")
+            }
+            Methods::Trace => {
+                write!(f, "TRACE")
+            }
+            Methods::Connect => {
+                write!(f, "CONNECT")
+            }
+            Methods::Extension(method) => {
+                write!(f, "{}", method)
+            }
         }
     }
 }
 
+// method names are case-sensitive, `get` is an extension method
 impl From<&str> for Methods {
     fn from(method: &str) -> Self {
         match method {
             "GET" => Methods::Get,
+            "HEAD" => Methods::Head,
             "POST" => Methods::Post,
-            _ => Methods::Get,
+            "PUT" => Methods::Put,
+            "DELETE" => Methods::Delete,
+            "PATCH" => Methods::Patch,
+            "OPTIONS" => Methods::Options,
+            "TRACE" => Methods::Trace,
+            "CONNECT" => Methods::Connect,
+            _ => Methods::Extension(method.to_string()),
         }
     }
 }
@@ -32,22 +72,28 @@ impl From<&str> for Methods {
 #[cfg(test)]
 mod test {
     use crate::Methods;
-    use crate::Methods::{Get, Post};
+    use crate::Methods::{Delete, Extension, Get, Post};
 
     #[test]
     fn method_to_str() {
         assert_eq!(Get.to_string().as_str(), "GET");
         assert_eq!(Post.to_string().as_str(), "POST");
+        assert_eq!(Delete.to_string().as_str(), "DELETE");
+        assert_eq!(Extension("PURGE".to_string()).to_string().as_str(), "PURGE");
     }
 
     #[test]
     fn str_to_method() {
         let get: Methods = "GET".into();
         let post: Methods = "POST".into();
+        let delete: Methods = "DELETE".into();
         let other: Methods = "AAA".into();
+        let lowercase: Methods = "get".into();
 
-        assert_eq!(get.to_string(), Get.to_string());
-        assert_eq!(post.to_string(), Post.to_string());
-        assert_eq!(other.to_string(), Get.to_string());
+        assert_eq!(get, Get);
+        assert_eq!(post, Post);
+        assert_eq!(delete, Delete);
+        assert_eq!(other, Extension("AAA".to_string()));
+        assert_eq!(lowercase, Extension("get".to_string()));
     }
 }
@@ -0,0 +1,167 @@
+use crate::request_header::is_token;
+use crate::utils::base64_decode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer(String),
+    /// Any other scheme, carrying either a token68 or a list of parameters.
+    Other {
+        scheme: String,
+        token: Option<String>,
+        params: Vec<(String, String)>,
+    },
+}
+
+impl Authorization {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (scheme, credentials) = value
+            .split_once(' ')
+            .map(|(scheme, credentials)| (scheme, credentials.trim()))
+            .unwrap_or((value, ""));
+
+        if !is_token(scheme) {
+            return None;
+        }
+
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let decoded = String::from_utf8(base64_decode(credentials)?).ok()?;
+            let (username, password) = decoded.split_once(':')?;
+
+            return Some(Authorization::Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            });
+        }
+
+        if scheme.eq_ignore_ascii_case("Bearer") {
+            return is_token68(credentials).then(|| Authorization::Bearer(credentials.to_string()));
+        }
+
+        let (token, params) = match credentials {
+            "" => (None, vec![]),
+            token if is_token68(token) => (Some(token.to_string()), vec![]),
+            params => (None, parse_auth_params(params)?),
+        };
+
+        Some(Authorization::Other {
+            scheme: scheme.to_string(),
+            token,
+            params,
+        })
+    }
+}
+
+fn is_token68(str: &str) -> bool {
+    let trimmed = str.trim_end_matches('=');
+
+    !trimmed.is_empty()
+        && trimmed
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~+/".contains(&b))
+}
+
+// `key=value` pairs separated by commas, values may be quoted strings.
+fn parse_auth_params(str: &str) -> Option<Vec<(String, String)>> {
+    let mut params = vec![];
+    let mut rest = str.trim();
+
+    while !rest.is_empty() {
+        let (key, after) = rest.split_once('=')?;
+        let key = key.trim();
+        let after = after.trim_start();
+
+        if !is_token(key) {
+            return None;
+        }
+
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let mut value = String::new();
+                let mut chars = quoted.char_indices();
+
+                loop {
+                    match chars.next()? {
+                        (_, '\\') => value.push(chars.next()?.1),
+                        (i, '"') => break (value, &quoted[i + 1..]),
+                        (_, c) => value.push(c),
+                    }
+                }
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (after[0..end].trim().to_string(), &after[end..])
+            }
+        };
+
+        params.push((key.to_ascii_lowercase(), value));
+
+        let after = after.trim_start();
+        rest = match after.strip_prefix(',') {
+            Some(after) => after.trim_start(),
+            None if after.is_empty() => after,
+            None => return None,
+        };
+    }
+
+    Some(params)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::auth::Authorization;
+
+    #[test]
+    fn parse_basic_credentials() {
+        assert_eq!(
+            Authorization::parse("Basic dXNlcjpwYTpzcw=="),
+            Some(Authorization::Basic {
+                username: "user".to_string(),
+                password: "pa:ss".to_string()
+            })
+        );
+        assert_eq!(Authorization::parse("basic dXNlcg=="), None);
+        assert_eq!(Authorization::parse("Basic not*base64"), None);
+    }
+
+    #[test]
+    fn parse_bearer_token() {
+        assert_eq!(
+            Authorization::parse("Bearer eyJhbGciOi.J9-_x~y+z/w=="),
+            Some(Authorization::Bearer(
+                "eyJhbGciOi.J9-_x~y+z/w==".to_string()
+            ))
+        );
+        assert_eq!(Authorization::parse("Bearer"), None);
+        assert_eq!(Authorization::parse("Bearer a b"), None);
+    }
+
+    #[test]
+    fn parse_other_schemes() {
+        assert_eq!(
+            Authorization::parse(r#"Digest username="Mufasa", realm="a, \"b\"", nc=00000001"#),
+            Some(Authorization::Other {
+                scheme: "Digest".to_string(),
+                token: None,
+                params: vec![
+                    ("username".to_string(), "Mufasa".to_string()),
+                    ("realm".to_string(), "a, \"b\"".to_string()),
+                    ("nc".to_string(), "00000001".to_string()),
+                ]
+            })
+        );
+        assert_eq!(
+            Authorization::parse("Negotiate YIIB=="),
+            Some(Authorization::Other {
+                scheme: "Negotiate".to_string(),
+                token: Some("YIIB==".to_string()),
+                params: vec![]
+            })
+        );
+        assert_eq!(Authorization::parse(r#"Digest realm="open"#), None);
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Error, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A request body read straight from the connection instead of being
+/// buffered, see `RequestParser::stream_body`.
+pub struct BodyReader {
+    inner: Box<dyn Read + Send>,
+    read: usize,
+    limit: usize,
+}
+
+/// The error a `BodyReader` fails with once the body grows past its limit,
+/// wrapped in an `io::Error`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyTooLarge {
+    pub limit: usize,
+}
+
+impl BodyTooLarge {
+    /// Whether `error` was caused by a body over the limit.
+    pub fn caused(error: &Error) -> bool {
+        error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<BodyTooLarge>())
+    }
+}
+
+impl Display for BodyTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request body exceeds {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+impl BodyReader {
+    pub(crate) fn new(inner: impl Read + Send + 'static, limit: usize) -> Self {
+        Self {
+            inner: Box::new(inner),
+            read: 0,
+            limit,
+        }
+    }
+
+    pub fn spool(&mut self) -> Result<TempFile, Error> {
+        let mut temp = TempFile::new()?;
+
+        io::copy(self, &mut temp.file)?;
+        temp.file.seek(SeekFrom::Start(0))?;
+
+        Ok(temp)
+    }
+}
+
+impl Read for BodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let length = self.inner.read(buf)?;
+
+        self.read += length;
+
+        if self.read > self.limit {
+            let limit = self.limit;
+            return Err(Error::new(ErrorKind::InvalidData, BodyTooLarge { limit }));
+        }
+
+        Ok(length)
+    }
+}
+
+impl Debug for BodyReader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BodyReader")
+            .field("read", &self.read)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+pub(crate) struct LengthReader<R> {
+    inner: R,
+    remaining: usize,
+}
+
+impl<R: Read> LengthReader<R> {
+    pub fn new(inner: R, length: usize) -> Self {
+        Self {
+            inner,
+            remaining: length,
+        }
+    }
+}
+
+impl<R: Read> Read for LengthReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let length = buf.len().min(self.remaining);
+        let length = self.inner.read(&mut buf[0..length])?;
+
+        if length == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+
+        self.remaining -= length;
+
+        Ok(length)
+    }
+}
+
+/// A file in the system temporary directory that is removed on drop
+/// unless it was persisted.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl TempFile {
+    fn new() -> Result<Self, Error> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let path = std::env::temp_dir().join(format!(
+            "simple-http-server-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+
+        // the body may be private, other users must not read it meanwhile
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let file = options.open(&path)?;
+
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn file(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    pub fn len(&self) -> Result<u64, Error> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn persist(self, target: impl AsRef<Path>) -> Result<(), Error> {
+        // renaming fails across file systems, the copy is cleaned up on drop
+        fs::rename(&self.path, target.as_ref())
+            .or_else(|_| fs::copy(&self.path, target.as_ref()).map(|_| ()))
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.file.read(buf)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::body_reader::{BodyReader, BodyTooLarge, LengthReader};
+    use crate::chunked::ChunkedReader;
+    use std::io::{ErrorKind, Read};
+
+    #[test]
+    fn read_body_with_length() {
+        let data: &[u8] = b"hello world";
+        let mut reader = BodyReader::new(LengthReader::new(data, 5), 10);
+        let mut body = String::new();
+
+        reader.read_to_string(&mut body).unwrap();
+
+        assert_eq!(body, "hello");
+    }
+
+    #[test]
+    fn read_body_over_limit() {
+        let data: &[u8] = b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let mut reader = BodyReader::new(ChunkedReader::new(data), 8);
+        let mut body = vec![];
+
+        let e = reader.read_to_end(&mut body).unwrap_err();
+
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        assert!(BodyTooLarge::caused(&e));
+
+        let data: &[u8] = b"zz\r\nhello\r\n0\r\n\r\n";
+        let e = BodyReader::new(ChunkedReader::new(data), 8)
+            .read_to_end(&mut body)
+            .unwrap_err();
+
+        assert!(!BodyTooLarge::caused(&e));
+    }
+
+    #[test]
+    fn spool_body_to_temp_file() {
+        let data: &[u8] = b"3\r\nabc\r\n3\r\ndef\r\n0\r\n\r\n";
+        let mut reader = BodyReader::new(ChunkedReader::new(data), 10);
+
+        let mut temp = reader.spool().unwrap();
+        let path = temp.path().to_path_buf();
+        let mut content = String::new();
+
+        assert_eq!(temp.len().unwrap(), 6);
+        temp.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "abcdef");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = temp.file().metadata().unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        drop(temp);
+        assert!(!path.exists());
+    }
+}
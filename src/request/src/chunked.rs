@@ -0,0 +1,407 @@
+use crate::utils::find;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Error, ErrorKind, Read};
+
+const CRLF: &[u8] = b"\r\n";
+const MAX_LINE_LENGTH: u64 = 8 * 1024;
+
+#[derive(Debug, PartialEq)]
+pub enum ChunkedError {
+    InvalidChunkSize,
+    InvalidChunkTerminator,
+    InvalidTrailer,
+    TrailersTooLarge,
+    TooLarge,
+}
+
+impl Display for ChunkedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkedError::InvalidChunkSize => write!(f, "invalid chunk size"),
+            ChunkedError::InvalidChunkTerminator => write!(f, "chunk data is not followed by CRLF"),
+            ChunkedError::InvalidTrailer => write!(f, "invalid trailer field"),
+            ChunkedError::TrailersTooLarge => write!(f, "trailer fields too large"),
+            ChunkedError::TooLarge => write!(f, "decoded body exceeds the size limit"),
+        }
+    }
+}
+
+/// Decodes a chunked body handed over in pieces, it picks up where the
+/// previous piece stopped.
+#[derive(Debug)]
+pub(crate) struct ChunkedDecoder {
+    state: ChunkState,
+    content: Vec<u8>,
+    trailers: Vec<(String, String)>,
+    max_trailer_count: usize,
+    max_trailer_size: usize,
+}
+
+impl ChunkedDecoder {
+    /// Trailers are held to the same limits as header fields.
+    pub(crate) fn new(max_trailer_count: usize, max_trailer_size: usize) -> Self {
+        Self {
+            state: ChunkState::Size,
+            content: vec![],
+            trailers: vec![],
+            max_trailer_count,
+            max_trailer_size,
+        }
+    }
+
+    /// Consumes as much of `data` as it can, returns the number of bytes
+    /// used. The rest has to be handed over again with more data appended.
+    pub(crate) fn feed(&mut self, data: &[u8], max_size: usize) -> Result<usize, ChunkedError> {
+        let mut position = 0;
+
+        loop {
+            match self.state {
+                ChunkState::Size => {
+                    let line = match next_line(data, position, ChunkedError::InvalidChunkSize)? {
+                        None => break,
+                        Some(line) => line,
+                    };
+
+                    position += line.len() + CRLF.len();
+
+                    self.state = match parse_chunk_size(line)? {
+                        0 => ChunkState::Trailers,
+                        size if size > max_size.saturating_sub(self.content.len()) => {
+                            return Err(ChunkedError::TooLarge)
+                        }
+                        size => ChunkState::Data(size),
+                    };
+                }
+                ChunkState::Data(remaining) => {
+                    let length = remaining.min(data.len() - position);
+
+                    if length == 0 {
+                        break;
+                    }
+
+                    self.content
+                        .extend_from_slice(&data[position..position + length]);
+                    position += length;
+
+                    self.state = match remaining - length {
+                        0 => ChunkState::DataEnd,
+                        remaining => ChunkState::Data(remaining),
+                    };
+                }
+                ChunkState::DataEnd => {
+                    if data.len() - position < CRLF.len() {
+                        break;
+                    }
+
+                    if &data[position..position + CRLF.len()] != CRLF {
+                        return Err(ChunkedError::InvalidChunkTerminator);
+                    }
+
+                    position += CRLF.len();
+                    self.state = ChunkState::Size;
+                }
+                ChunkState::Trailers => {
+                    let rest = &data[position..];
+                    let line = match find(rest, CRLF) {
+                        Some(end) => &rest[..end],
+                        None if rest.len() > self.max_trailer_size.saturating_add(CRLF.len()) => {
+                            return Err(ChunkedError::TrailersTooLarge)
+                        }
+                        None => break,
+                    };
+
+                    position += line.len() + CRLF.len();
+
+                    if line.is_empty() {
+                        self.state = ChunkState::Done;
+                        continue;
+                    }
+
+                    if line.len() > self.max_trailer_size
+                        || self.trailers.len() >= self.max_trailer_count
+                    {
+                        return Err(ChunkedError::TrailersTooLarge);
+                    }
+
+                    let line = String::from_utf8_lossy(line);
+                    let (key, value) = line.split_once(':').ok_or(ChunkedError::InvalidTrailer)?;
+
+                    self.trailers
+                        .push((key.trim().to_string(), value.trim().to_string()));
+                }
+                ChunkState::Done => break,
+            }
+        }
+
+        Ok(position)
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.state == ChunkState::Done
+    }
+
+    /// Takes the decoded content and trailers out.
+    pub(crate) fn take(&mut self) -> (Vec<u8>, Vec<(String, String)>) {
+        (
+            std::mem::take(&mut self.content),
+            std::mem::take(&mut self.trailers),
+        )
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum ChunkState {
+    #[default]
+    Size,
+    Data(usize),
+    DataEnd,
+    Trailers,
+    Done,
+}
+
+/// Decodes a chunked body while it is read from `inner`, trailers are skipped.
+pub struct ChunkedReader<R> {
+    inner: R,
+    state: ChunkState,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            state: ChunkState::Size,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Vec<u8>, Error> {
+        let mut line = vec![];
+
+        (&mut self.inner)
+            .take(MAX_LINE_LENGTH)
+            .read_until(b'\n', &mut line)?;
+
+        match line.strip_suffix(CRLF) {
+            Some(line) => Ok(line.to_vec()),
+            None if line.is_empty() => Err(ErrorKind::UnexpectedEof.into()),
+            None => Err(Error::new(ErrorKind::InvalidData, "malformed chunk line")),
+        }
+    }
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        loop {
+            match self.state {
+                ChunkState::Size => {
+                    let line = self.next_line()?;
+                    let size = parse_chunk_size(&line)
+                        .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+
+                    self.state = match size {
+                        0 => ChunkState::Trailers,
+                        size => ChunkState::Data(size),
+                    };
+                }
+                ChunkState::Data(remaining) => {
+                    if buf.is_empty() {
+                        return Ok(0);
+                    }
+
+                    let length = buf.len().min(remaining);
+                    let length = self.inner.read(&mut buf[0..length])?;
+
+                    if length == 0 {
+                        return Err(ErrorKind::UnexpectedEof.into());
+                    }
+
+                    self.state = match remaining - length {
+                        0 => ChunkState::DataEnd,
+                        remaining => ChunkState::Data(remaining),
+                    };
+
+                    return Ok(length);
+                }
+                ChunkState::DataEnd => {
+                    if !self.next_line()?.is_empty() {
+                        let e = ChunkedError::InvalidChunkTerminator.to_string();
+                        return Err(Error::new(ErrorKind::InvalidData, e));
+                    }
+
+                    self.state = ChunkState::Size;
+                }
+                ChunkState::Trailers => {
+                    while !self.next_line()?.is_empty() {}
+
+                    self.state = ChunkState::Done;
+                }
+                ChunkState::Done => return Ok(0),
+            }
+        }
+    }
+}
+
+// The complete line starting at `start`, an incomplete one that is already
+// too long to be valid fails with `error`.
+fn next_line(
+    data: &[u8],
+    start: usize,
+    error: ChunkedError,
+) -> Result<Option<&[u8]>, ChunkedError> {
+    match find(&data[start..], CRLF) {
+        Some(end) => Ok(Some(&data[start..start + end])),
+        None if (data.len() - start) as u64 > MAX_LINE_LENGTH => Err(error),
+        None => Ok(None),
+    }
+}
+
+fn parse_chunk_size(line: &[u8]) -> Result<usize, ChunkedError> {
+    let line = std::str::from_utf8(line).map_err(|_| ChunkedError::InvalidChunkSize)?;
+
+    // chunk extensions (`;name=value`) carry nothing we act on
+    let size = line.split(';').next().unwrap_or_default().trim();
+
+    if size.is_empty() || !size.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ChunkedError::InvalidChunkSize);
+    }
+
+    usize::from_str_radix(size, 16).map_err(|_| ChunkedError::InvalidChunkSize)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chunked::{ChunkedDecoder, ChunkedError, ChunkedReader};
+    use std::io::{ErrorKind, Read};
+
+    type Decoded = (Vec<u8>, Vec<(String, String)>, usize);
+
+    // Decodes `data` in one go, `None` while the body is incomplete.
+    fn decode(data: &[u8], max_size: usize) -> Result<Option<Decoded>, ChunkedError> {
+        let mut decoder = ChunkedDecoder::new(100, 8 * 1024);
+        let used = decoder.feed(data, max_size)?;
+
+        if !decoder.is_done() {
+            return Ok(None);
+        }
+
+        let (content, trailers) = decoder.take();
+
+        Ok(Some((content, trailers, used)))
+    }
+
+    #[test]
+    fn decode_chunked_body() {
+        let data = b"4\r\nWiki\r\n5\r\npedia\r\nE\r\n in\r\n\r\nchunks.\r\n0\r\n\r\n";
+        let (content, trailers, used) = decode(data, usize::MAX).unwrap().unwrap();
+
+        assert_eq!(content, b"Wikipedia in\r\n\r\nchunks.");
+        assert_eq!(used, data.len());
+        assert!(trailers.is_empty());
+    }
+
+    #[test]
+    fn decode_chunked_body_with_extensions_and_trailers() {
+        let data = b"3;name=value\r\nabc\r\n0\r\nExpires: never\r\nX-Sum: 1\r\n\r\nGET";
+        let (content, trailers, used) = decode(data, usize::MAX).unwrap().unwrap();
+
+        assert_eq!(content, b"abc");
+        assert_eq!(used, data.len() - 3);
+        assert_eq!(
+            trailers,
+            vec![
+                ("Expires".to_string(), "never".to_string()),
+                ("X-Sum".to_string(), "1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_incomplete_chunked_body() {
+        assert!(decode(b"4\r\nWi", usize::MAX).unwrap().is_none());
+        assert!(decode(b"4\r\nWiki\r\n0\r\n", usize::MAX).unwrap().is_none());
+    }
+
+    #[test]
+    fn decode_chunked_body_over_limit() {
+        assert_eq!(
+            decode(b"3\r\nabc\r\n8\r\n", 10).unwrap_err(),
+            ChunkedError::TooLarge
+        );
+        assert!(decode(b"3\r\nabc\r\n0\r\n\r\n", 3).unwrap().is_some());
+    }
+
+    #[test]
+    fn decode_chunked_body_in_pieces() {
+        let data = b"4\r\nWiki\r\n5\r\npedia\r\n0\r\nX-Sum: 1\r\n\r\n";
+        let mut decoder = ChunkedDecoder::new(100, 8 * 1024);
+        let mut buffer = vec![];
+
+        for byte in data {
+            buffer.push(*byte);
+
+            let used = decoder.feed(&buffer, 9).unwrap();
+            buffer.drain(0..used);
+        }
+
+        assert!(decoder.is_done());
+        assert!(buffer.is_empty());
+        assert_eq!(
+            decoder.take(),
+            (
+                b"Wikipedia".to_vec(),
+                vec![("X-Sum".to_string(), "1".to_string())]
+            )
+        );
+    }
+
+    #[test]
+    fn decode_chunked_body_with_too_many_trailers() {
+        let data = b"0\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+        let mut decoder = ChunkedDecoder::new(2, 16);
+
+        assert_eq!(
+            decoder.feed(data, usize::MAX).unwrap_err(),
+            ChunkedError::TrailersTooLarge
+        );
+
+        let data = [b"0\r\nX-Long: ".as_slice(), &[b'a'; 32]].concat();
+        let mut decoder = ChunkedDecoder::new(2, 16);
+
+        assert_eq!(
+            decoder.feed(&data, usize::MAX).unwrap_err(),
+            ChunkedError::TrailersTooLarge
+        );
+    }
+
+    #[test]
+    fn decode_invalid_chunked_body() {
+        assert_eq!(
+            decode(b"zz\r\n", usize::MAX).unwrap_err(),
+            ChunkedError::InvalidChunkSize
+        );
+        assert_eq!(
+            decode(b"2\r\nabc\r\n", usize::MAX).unwrap_err(),
+            ChunkedError::InvalidChunkTerminator
+        );
+    }
+
+    #[test]
+    fn read_chunked_body() {
+        let data: &[u8] = b"4\r\nWiki\r\n5;ext=1\r\npedia\r\n0\r\nX-Sum: 1\r\n\r\nnext";
+        let mut reader = ChunkedReader::new(data);
+        let mut body = String::new();
+
+        reader.read_to_string(&mut body).unwrap();
+
+        assert_eq!(body, "Wikipedia");
+    }
+
+    #[test]
+    fn read_truncated_chunked_body() {
+        let data: &[u8] = b"4\r\nWi";
+        let mut body = vec![];
+
+        let e = ChunkedReader::new(data).read_to_end(&mut body).unwrap_err();
+
+        assert_eq!(e.kind(), ErrorKind::UnexpectedEof);
+    }
+}
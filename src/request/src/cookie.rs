@@ -0,0 +1,78 @@
+use crate::utils::unquote;
+
+#[derive(Debug, Default, Clone)]
+pub struct Cookies {
+    entries: Vec<(String, String)>,
+}
+
+impl Cookies {
+    pub fn parse<'a>(headers: impl IntoIterator<Item = &'a str>) -> Self {
+        let entries = headers
+            .into_iter()
+            .flat_map(|header| header.split(';'))
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (name.trim(), unquote(value.trim())))
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|(key, _)| key == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cookie::Cookies;
+
+    #[test]
+    fn parse_cookies() {
+        let cookies = Cookies::parse(["session=abc123; theme=\"dark mode\"; lang=en"]);
+
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies.get("session"), Some("abc123"));
+        assert_eq!(cookies.get("theme"), Some("dark mode"));
+        assert_eq!(cookies.get("Session"), None);
+        assert!(cookies.contains("lang"));
+    }
+
+    #[test]
+    fn parse_cookies_from_multiple_headers() {
+        let cookies = Cookies::parse(["a=1; b=2", "c=3;;broken; =x; d=a=b"]);
+
+        assert_eq!(
+            cookies.iter().collect::<Vec<(&str, &str)>>(),
+            vec![("a", "1"), ("b", "2"), ("c", "3"), ("d", "a=b")]
+        );
+    }
+
+    #[test]
+    fn parse_empty_cookies() {
+        assert!(Cookies::parse([]).is_empty());
+        assert!(Cookies::parse([""]).is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::ParseError;
+use crate::request_header::Header;
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use std::io::{Cursor, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Coding {
+    Gzip,
+    Deflate,
+}
+
+impl Coding {
+    /// Reads the `Content-Encoding` fields, codings are listed in the order
+    /// they were applied.
+    pub(crate) fn parse_all(headers: &Header) -> Result<Vec<Coding>, ParseError> {
+        headers
+            .get_all("Content-Encoding")
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|coding| !coding.is_empty() && !coding.eq_ignore_ascii_case("identity"))
+            .map(|coding| match coding.to_ascii_lowercase().as_str() {
+                "gzip" | "x-gzip" => Ok(Coding::Gzip),
+                "deflate" => Ok(Coding::Deflate),
+                _ => Err(ParseError::UnsupportedEncoding(coding.to_string())),
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn decoder(
+    reader: impl Read + Send + 'static,
+    codings: &[Coding],
+) -> Box<dyn Read + Send> {
+    codings
+        .iter()
+        .rev()
+        .fold(Box::new(reader), |reader, coding| match coding {
+            Coding::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Coding::Deflate => Box::new(ZlibDecoder::new(reader)),
+        })
+}
+
+/// Undoes the codings of a buffered body, decoding stops as soon as the
+/// output grows past `limit`.
+pub(crate) fn decode(
+    content: Vec<u8>,
+    codings: &[Coding],
+    limit: usize,
+) -> Result<Vec<u8>, ParseError> {
+    if codings.is_empty() {
+        return Ok(content);
+    }
+
+    let mut decoded = vec![];
+
+    decoder(Cursor::new(content), codings)
+        .take((limit as u64).saturating_add(1))
+        .read_to_end(&mut decoded)
+        .map_err(|_| ParseError::BadEncoding)?;
+
+    if decoded.len() > limit {
+        return Err(ParseError::BodyTooLarge);
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::encoding::{decode, Coding};
+    use crate::{Header, ParseError};
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn parse_codings() {
+        let mut headers = Header::new();
+        headers.insert("Content-Encoding", "deflate, identity");
+        headers.insert("Content-Encoding", "X-Gzip");
+
+        assert_eq!(
+            Coding::parse_all(&headers),
+            Ok(vec![Coding::Deflate, Coding::Gzip])
+        );
+
+        headers.insert("Content-Encoding", "br");
+
+        assert_eq!(
+            Coding::parse_all(&headers),
+            Err(ParseError::UnsupportedEncoding("br".to_string()))
+        );
+    }
+
+    #[test]
+    fn decode_stacked_codings() {
+        let data = br#"{"name": "value"}"#;
+
+        assert_eq!(decode(gzip(data), &[Coding::Gzip], 64).unwrap(), data);
+        assert_eq!(
+            decode(gzip(&deflate(data)), &[Coding::Deflate, Coding::Gzip], 64).unwrap(),
+            data
+        );
+        assert_eq!(
+            decode(data.to_vec(), &[Coding::Gzip], 64),
+            Err(ParseError::BadEncoding)
+        );
+    }
+
+    #[test]
+    fn decode_over_limit() {
+        let bomb = gzip(&[0; 1024 * 1024]);
+
+        assert!(bomb.len() < 4096);
+        assert_eq!(
+            decode(bomb, &[Coding::Gzip], 4096),
+            Err(ParseError::BodyTooLarge)
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use crate::chunked::ChunkedError;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Incomplete,
+    BadRequestLine,
+    BadHeader,
+    MissingHost,
+    InvalidHost,
+    InvalidVersion,
+    InvalidContentLength,
+    BadChunk(ChunkedError),
+    UriTooLong,
+    HeadersTooLarge,
+    BodyTooLarge,
+    UnsupportedEncoding(String),
+    UnsupportedTransferCoding(String),
+    BadEncoding,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Incomplete => write!(f, "incomplete request"),
+            ParseError::BadRequestLine => write!(f, "malformed request line"),
+            ParseError::BadHeader => write!(f, "malformed header field"),
+            ParseError::MissingHost => write!(f, "missing `Host` header"),
+            ParseError::InvalidHost => write!(f, "invalid `Host` header"),
+            ParseError::InvalidVersion => write!(f, "unsupported HTTP version"),
+            ParseError::InvalidContentLength => write!(f, "invalid `Content-Length`"),
+            ParseError::BadChunk(e) => write!(f, "malformed chunked body: {}", e),
+            ParseError::UriTooLong => write!(f, "request target too long"),
+            ParseError::HeadersTooLarge => write!(f, "header fields too large"),
+            ParseError::BodyTooLarge => write!(f, "request body too large"),
+            ParseError::UnsupportedEncoding(coding) => {
+                write!(f, "unsupported content coding `{}`", coding)
+            }
+            ParseError::UnsupportedTransferCoding(coding) => {
+                write!(f, "unsupported transfer coding `{}`", coding)
+            }
+            ParseError::BadEncoding => write!(f, "malformed encoded body"),
+        }
+    }
+}
+
+impl From<ChunkedError> for ParseError {
+    fn from(e: ChunkedError) -> Self {
+        match e {
+            ChunkedError::TooLarge => ParseError::BodyTooLarge,
+            ChunkedError::TrailersTooLarge => ParseError::HeadersTooLarge,
+            e => ParseError::BadChunk(e),
+        }
+    }
+}
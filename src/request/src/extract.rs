@@ -0,0 +1,420 @@
+use json::JsonValue;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub struct ExtractError {
+    field: Option<String>,
+    message: String,
+}
+
+impl ExtractError {
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn within(mut self, segment: &str) -> Self {
+        self.field = Some(match self.field {
+            None => segment.to_string(),
+            Some(field) if field.starts_with('[') => format!("{}{}", segment, field),
+            Some(field) => format!("{}.{}", segment, field),
+        });
+        self
+    }
+}
+
+impl Display for ExtractError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.field {
+            None => write!(f, "{}", self.message),
+            Some(field) => write!(f, "field `{}`: {}", field, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl de::Error for ExtractError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            field: None,
+            message: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            field: Some(field.to_string()),
+            message: String::from("missing field"),
+        }
+    }
+}
+
+/// Deserializes a parsed query or body into `T`.
+///
+/// Form values always arrive as strings, so numbers and booleans are also
+/// accepted in their textual form, and a single value fills a sequence.
+pub fn from_value<T: DeserializeOwned>(value: &JsonValue) -> Result<T, ExtractError> {
+    T::deserialize(ValueDeserializer { value })
+}
+
+struct ValueDeserializer<'a> {
+    value: &'a JsonValue,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn invalid<T>(&self, expected: &str) -> Result<T, ExtractError> {
+        let found = match self.value {
+            JsonValue::Null => String::from("null"),
+            JsonValue::Object(_) => String::from("an object"),
+            JsonValue::Array(_) => String::from("an array"),
+            value => format!("`{}`", value),
+        };
+
+        Err(de::Error::custom(format!(
+            "expected {}, found {}",
+            expected, found
+        )))
+    }
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $ty:ty, $as:ident) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ExtractError> {
+            let number = match self.value {
+                JsonValue::Number(_) => self.value.$as(),
+                JsonValue::Short(_) | JsonValue::String(_) => self
+                    .value
+                    .as_str()
+                    .and_then(|s| s.trim().parse::<$ty>().ok()),
+                _ => None,
+            };
+
+            match number {
+                Some(number) => visitor.$visit(number),
+                None => self.invalid(stringify!($ty)),
+            }
+        }
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = ExtractError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ExtractError> {
+        match self.value {
+            JsonValue::Null => visitor.visit_unit(),
+            JsonValue::Short(_) | JsonValue::String(_) => {
+                visitor.visit_str(self.value.as_str().unwrap_or_default())
+            }
+            JsonValue::Boolean(b) => visitor.visit_bool(*b),
+            JsonValue::Number(_) => match (self.value.as_u64(), self.value.as_i64()) {
+                (Some(n), _) => visitor.visit_u64(n),
+                (_, Some(n)) => visitor.visit_i64(n),
+                _ => visitor.visit_f64(self.value.as_f64().unwrap_or_default()),
+            },
+            JsonValue::Object(_) => visitor.visit_map(MapDeserializer {
+                entries: self.value.entries(),
+                value: None,
+            }),
+            JsonValue::Array(values) => visitor.visit_seq(SeqDeserializer {
+                values: values.iter(),
+                index: 0,
+            }),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ExtractError> {
+        let value = match self.value {
+            JsonValue::Boolean(b) => Some(*b),
+            JsonValue::Short(_) | JsonValue::String(_) => {
+                match self.value.as_str().unwrap_or_default() {
+                    "true" | "on" | "1" => Some(true),
+                    "false" | "off" | "0" => Some(false),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        match value {
+            Some(b) => visitor.visit_bool(b),
+            None => self.invalid("a boolean"),
+        }
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8, as_i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16, as_i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32, as_i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64, as_i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8, as_u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16, as_u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32, as_u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64, as_u64);
+    deserialize_number!(deserialize_f32, visit_f32, f32, as_f32);
+    deserialize_number!(deserialize_f64, visit_f64, f64, as_f64);
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ExtractError> {
+        match self.value {
+            JsonValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ExtractError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ExtractError> {
+        match self.value {
+            JsonValue::Array(_) => self.deserialize_any(visitor),
+            JsonValue::Null => visitor.visit_seq(SeqDeserializer {
+                values: [].iter(),
+                index: 0,
+            }),
+            value => visitor.visit_seq(SeqDeserializer {
+                values: std::slice::from_ref(value).iter(),
+                index: 0,
+            }),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ExtractError> {
+        match self.value {
+            JsonValue::Object(_) => self.deserialize_any(visitor),
+            _ => self.invalid("an object"),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ExtractError> {
+        match self.value {
+            JsonValue::Short(_) | JsonValue::String(_) => {
+                visitor.visit_enum(self.value.as_str().unwrap_or_default().into_deserializer())
+            }
+            JsonValue::Object(_) if self.value.len() == 1 => {
+                let (variant, value) = self.value.entries().next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            _ => self.invalid("an enum variant"),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct map
+        identifier ignored_any
+    }
+}
+
+struct MapDeserializer<'a> {
+    entries: json::iterators::Entries<'a>,
+    value: Option<(&'a str, &'a JsonValue)>,
+}
+
+impl<'de, 'a> MapAccess<'de> for MapDeserializer<'a> {
+    type Error = ExtractError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ExtractError> {
+        match self.entries.next() {
+            None => Ok(None),
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, ExtractError> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+
+        seed.deserialize(ValueDeserializer { value })
+            .map_err(|e| e.within(key))
+    }
+}
+
+struct SeqDeserializer<'a> {
+    values: std::slice::Iter<'a, JsonValue>,
+    index: usize,
+}
+
+impl<'de, 'a> SeqAccess<'de> for SeqDeserializer<'a> {
+    type Error = ExtractError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ExtractError> {
+        match self.values.next() {
+            None => Ok(None),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+
+                seed.deserialize(ValueDeserializer { value })
+                    .map(Some)
+                    .map_err(|e| e.within(&format!("[{}]", index)))
+            }
+        }
+    }
+}
+
+struct EnumDeserializer<'a> {
+    variant: &'a str,
+    value: &'a JsonValue,
+}
+
+impl<'de, 'a> EnumAccess<'de> for EnumDeserializer<'a> {
+    type Error = ExtractError;
+    type Variant = ValueDeserializer<'a>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), ExtractError> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+
+        Ok((variant, ValueDeserializer { value: self.value }))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for ValueDeserializer<'a> {
+    type Error = ExtractError;
+
+    fn unit_variant(self) -> Result<(), ExtractError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, ExtractError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ExtractError> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ExtractError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extract::from_value;
+    use crate::utils::parse_kv;
+    use json::object;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Search {
+        q: String,
+        page: u32,
+        exact: bool,
+        tags: Vec<String>,
+        limit: Option<u8>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Role {
+        Admin,
+        Guest,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct User {
+        name: String,
+        age: u8,
+        role: Role,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Team {
+        members: Vec<User>,
+    }
+
+    #[test]
+    fn extract_from_form_strings() {
+        let search: Search = from_value(&parse_kv("q=rust&page=2&exact=on&tags=web")).unwrap();
+
+        assert_eq!(
+            search,
+            Search {
+                q: "rust".to_string(),
+                page: 2,
+                exact: true,
+                tags: vec!["web".to_string()],
+                limit: None,
+            }
+        );
+    }
+
+    #[test]
+    fn extract_from_json() {
+        let user: User = from_value(&object! {name: "a", age: 7, role: "admin"}).unwrap();
+
+        assert_eq!(
+            user,
+            User {
+                name: "a".to_string(),
+                age: 7,
+                role: Role::Admin,
+            }
+        );
+    }
+
+    #[test]
+    fn extract_error_names_the_field() {
+        let error = from_value::<Search>(&parse_kv("q=rust&page=two&exact=1")).unwrap_err();
+        assert_eq!(error.field(), Some("page"));
+        assert_eq!(error.to_string(), "field `page`: expected u32, found `two`");
+
+        let error = from_value::<Search>(&parse_kv("q=rust&page=1")).unwrap_err();
+        assert_eq!(error.field(), Some("exact"));
+        assert_eq!(error.message(), "missing field");
+
+        let team = object! {members: [{name: "a", age: 1, role: "guest"}, {name: "b", age: 300, role: "guest"}]};
+        let error = from_value::<Team>(&team).unwrap_err();
+        assert_eq!(error.field(), Some("members[1].age"));
+    }
+}
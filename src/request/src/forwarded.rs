@@ -0,0 +1,406 @@
+use crate::request_header::Header;
+use crate::utils::unquote;
+use std::net::IpAddr;
+
+/// The forwarding fields the trusted proxies set. Only those are read, a
+/// client can send any of them and a proxy passes on what it does not set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForwardingHeaders {
+    Forwarded,
+    /// `X-Forwarded-For`, with `X-Forwarded-Proto` and `X-Forwarded-Host`
+    /// read only when the proxies overwrite them.
+    XForwarded {
+        proto: bool,
+        host: bool,
+    },
+}
+
+impl Default for ForwardingHeaders {
+    fn default() -> Self {
+        ForwardingHeaders::XForwarded {
+            proto: false,
+            host: false,
+        }
+    }
+}
+
+/// Networks of the proxies whose forwarding headers are believed, given
+/// as single addresses or in CIDR notation.
+#[derive(Debug, Default, Clone)]
+pub struct TrustedProxies {
+    networks: Vec<(IpAddr, u8)>,
+    headers: ForwardingHeaders,
+}
+
+impl TrustedProxies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_headers(&mut self, headers: ForwardingHeaders) {
+        self.headers = headers;
+    }
+
+    /// Adds `10.0.0.1` or `10.0.0.0/8` like networks, false when the
+    /// network is malformed.
+    pub fn add(&mut self, network: &str) -> bool {
+        let (address, prefix) = network.split_once('/').unwrap_or((network, ""));
+
+        let address = match address.trim().parse::<IpAddr>() {
+            Ok(address) => address,
+            Err(_) => return false,
+        };
+
+        let max = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        let prefix = match prefix {
+            "" => max,
+            prefix => match prefix.trim().parse::<u8>() {
+                Ok(prefix) if prefix <= max => prefix,
+                _ => return false,
+            },
+        };
+
+        // `::ffff:10.0.0.0/104` is kept as `10.0.0.0/8`, the form clients
+        // are compared in
+        let network = match canonical(&address) {
+            IpAddr::V4(v4) if address.is_ipv6() => match prefix.checked_sub(96) {
+                Some(prefix) => (IpAddr::V4(v4), prefix),
+                None => return false,
+            },
+            _ => (address, prefix),
+        };
+
+        self.networks.push(network);
+
+        true
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = canonical(ip);
+
+        self.networks
+            .iter()
+            .any(|(network, prefix)| match (network, ip) {
+                (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                    mask(u32::from(*network) as u128, *prefix, 32)
+                        == mask(u32::from(ip) as u128, *prefix, 32)
+                }
+                (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                    mask(u128::from(*network), *prefix, 128) == mask(u128::from(ip), *prefix, 128)
+                }
+                _ => false,
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.networks.is_empty()
+    }
+}
+
+fn canonical(ip: &IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(*ip, IpAddr::V4),
+        ip => *ip,
+    }
+}
+
+fn mask(bits: u128, prefix: u8, width: u8) -> u128 {
+    match prefix {
+        0 => 0,
+        prefix => bits >> (width - prefix),
+    }
+}
+
+/// What the trusted proxies report about the client.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Forwarding {
+    pub ip: Option<IpAddr>,
+    pub proto: Option<String>,
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Hop {
+    ip: Option<IpAddr>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+/// Walks the forwarding chain from the nearest proxy outwards, the client
+/// is the first hop that is not a trusted proxy.
+pub(crate) fn resolve(peer: IpAddr, headers: &Header, trusted: &TrustedProxies) -> Forwarding {
+    if !trusted.contains(&peer) {
+        return Forwarding {
+            ip: Some(peer),
+            ..Forwarding::default()
+        };
+    }
+
+    let hops = match trusted.headers {
+        ForwardingHeaders::Forwarded => forwarded_hops(&headers.get_all("Forwarded")),
+        ForwardingHeaders::XForwarded { proto, host } => {
+            x_forwarded_hops(peer, headers, proto, host)
+        }
+    };
+
+    let mut client = Hop {
+        ip: Some(peer),
+        ..Hop::default()
+    };
+
+    for hop in hops.into_iter().rev() {
+        let trusted_hop = hop.ip.is_some_and(|ip| trusted.contains(&ip));
+
+        client = hop;
+
+        if !trusted_hop {
+            break;
+        }
+    }
+
+    Forwarding {
+        ip: client.ip,
+        proto: client.proto,
+        host: client.host,
+    }
+}
+
+fn forwarded_hops(values: &[&str]) -> Vec<Hop> {
+    values
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|element| {
+            let mut hop = Hop::default();
+
+            for pair in element.split(';') {
+                let (key, value) = match pair.split_once('=') {
+                    Some((key, value)) => (key.trim(), unquote(value.trim())),
+                    None => continue,
+                };
+
+                if key.eq_ignore_ascii_case("for") {
+                    hop.ip = parse_node(value);
+                } else if key.eq_ignore_ascii_case("proto") {
+                    hop.proto = Some(value.to_ascii_lowercase());
+                } else if key.eq_ignore_ascii_case("host") {
+                    hop.host = Some(value.to_string());
+                }
+            }
+
+            hop
+        })
+        .collect()
+}
+
+// `X-Forwarded-Proto` and `X-Forwarded-Host` are set by the nearest proxy
+// only, they are attached to every hop.
+fn x_forwarded_hops(peer: IpAddr, headers: &Header, proto: bool, host: bool) -> Vec<Hop> {
+    let last = |name: &str, read: bool| {
+        if !read {
+            return None;
+        }
+
+        headers
+            .get_all(name)
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .rfind(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    let proto = last("X-Forwarded-Proto", proto).map(|proto| proto.to_ascii_lowercase());
+    let host = last("X-Forwarded-Host", host);
+
+    let mut hops = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|node| Hop {
+            ip: parse_node(node.trim()),
+            proto: proto.clone(),
+            host: host.clone(),
+        })
+        .collect::<Vec<Hop>>();
+
+    if hops.is_empty() {
+        hops.push(Hop {
+            ip: Some(peer),
+            proto,
+            host,
+        });
+    }
+
+    hops
+}
+
+// `192.0.2.1`, `192.0.2.1:8080`, `[2001:db8::1]:8080` or a bare IPv6
+// address, `unknown` and obfuscated identifiers yield `None`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+
+    match node.matches(':').count() {
+        1 => node.split_once(':')?.0.parse().ok(),
+        _ => node.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::forwarded::{resolve, Forwarding, ForwardingHeaders, TrustedProxies};
+    use crate::Header;
+    use std::net::IpAddr;
+
+    fn trusted() -> TrustedProxies {
+        let mut trusted = TrustedProxies::new();
+
+        assert!(trusted.add("127.0.0.1"));
+        assert!(trusted.add("10.0.0.0/8"));
+        assert!(!trusted.add("10.0.0.0/33"));
+        assert!(!trusted.add("proxy"));
+
+        trusted
+    }
+
+    fn trusted_with(headers: ForwardingHeaders) -> TrustedProxies {
+        let mut trusted = trusted();
+        trusted.set_headers(headers);
+
+        trusted
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn match_trusted_networks() {
+        let trusted = trusted();
+
+        assert!(trusted.contains(&ip("10.1.2.3")));
+        assert!(trusted.contains(&ip("::ffff:127.0.0.1")));
+        assert!(!trusted.contains(&ip("127.0.0.2")));
+        assert!(!trusted.contains(&ip("::1")));
+    }
+
+    #[test]
+    fn match_ipv4_mapped_networks() {
+        let mut trusted = TrustedProxies::new();
+
+        assert!(trusted.add("::ffff:192.168.0.0/112"));
+        assert!(!trusted.add("::ffff:10.0.0.0/64"));
+
+        assert!(trusted.contains(&ip("192.168.4.2")));
+        assert!(trusted.contains(&ip("::ffff:192.168.4.2")));
+        assert!(!trusted.contains(&ip("192.169.0.1")));
+        assert!(!trusted.contains(&ip("10.0.0.1")));
+    }
+
+    #[test]
+    fn resolve_x_forwarded_for() {
+        let proxies = trusted_with(ForwardingHeaders::XForwarded {
+            proto: true,
+            host: false,
+        });
+
+        let mut headers = Header::new();
+        headers.insert("X-Forwarded-For", "198.51.100.7, 203.0.113.9");
+        headers.insert("X-Forwarded-For", "10.0.0.2");
+        headers.insert("X-Forwarded-Proto", "HTTPS");
+        headers.insert("X-Forwarded-Host", "example.com");
+
+        assert_eq!(
+            resolve(ip("127.0.0.1"), &headers, &proxies),
+            Forwarding {
+                ip: Some(ip("203.0.113.9")),
+                proto: Some("https".to_string()),
+                host: None,
+            }
+        );
+        assert_eq!(
+            resolve(ip("127.0.0.1"), &headers, &trusted()),
+            Forwarding {
+                ip: Some(ip("203.0.113.9")),
+                ..Forwarding::default()
+            }
+        );
+
+        let mut proto_only = Header::new();
+        proto_only.insert("X-Forwarded-Proto", "https");
+
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &proto_only, &proxies),
+            Forwarding {
+                ip: Some(ip("10.0.0.1")),
+                proto: Some("https".to_string()),
+                host: None,
+            }
+        );
+        assert_eq!(
+            resolve(ip("192.0.2.1"), &headers, &trusted()),
+            Forwarding {
+                ip: Some(ip("192.0.2.1")),
+                ..Forwarding::default()
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_forwarded() {
+        let proxies = trusted_with(ForwardingHeaders::Forwarded);
+
+        let mut headers = Header::new();
+        headers.insert(
+            "Forwarded",
+            r#"for="[2001:db8::1]:4711";proto=https;host=example.com, for=10.0.0.3"#,
+        );
+        headers.insert("X-Forwarded-For", "192.0.2.1");
+
+        assert_eq!(
+            resolve(ip("127.0.0.1"), &headers, &proxies),
+            Forwarding {
+                ip: Some(ip("2001:db8::1")),
+                proto: Some("https".to_string()),
+                host: Some("example.com".to_string()),
+            }
+        );
+
+        let mut headers = Header::new();
+        headers.insert("Forwarded", "for=unknown");
+
+        assert_eq!(resolve(ip("127.0.0.1"), &headers, &proxies).ip, None);
+    }
+
+    #[test]
+    fn ignore_headers_the_proxies_do_not_set() {
+        let mut headers = Header::new();
+        headers.insert("Forwarded", "for=6.6.6.6;proto=https");
+        headers.insert("X-Forwarded-For", "203.0.113.5");
+
+        assert_eq!(
+            resolve(ip("127.0.0.1"), &headers, &trusted()).ip,
+            Some(ip("203.0.113.5"))
+        );
+
+        let mut headers = Header::new();
+        headers.insert("X-Forwarded-For", "6.6.6.6");
+
+        assert_eq!(
+            resolve(
+                ip("127.0.0.1"),
+                &headers,
+                &trusted_with(ForwardingHeaders::Forwarded)
+            ),
+            Forwarding {
+                ip: Some(ip("127.0.0.1")),
+                ..Forwarding::default()
+            }
+        );
+    }
+}
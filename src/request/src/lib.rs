@@ -1,70 +1,306 @@
+mod auth;
+mod body_reader;
+mod chunked;
+mod cookie;
+mod encoding;
+mod error;
+mod extract;
+mod forwarded;
+mod limits;
+mod multipart;
+mod negotiate;
+mod param;
+mod parser;
 mod request_body;
 mod request_header;
 mod request_line;
 mod utils;
 
+pub use crate::auth::Authorization;
+pub use crate::body_reader::{BodyReader, BodyTooLarge, TempFile};
+pub use crate::chunked::{ChunkedError, ChunkedReader};
+pub use crate::cookie::Cookies;
+pub use crate::error::ParseError;
+pub use crate::extract::ExtractError;
+use crate::forwarded::Forwarding;
+pub use crate::forwarded::{ForwardingHeaders, TrustedProxies};
+pub use crate::limits::Limits;
+pub use crate::multipart::{FilePart, Multipart, MultipartError};
+pub use crate::negotiate::Preference;
+pub use crate::param::FromParam;
+pub use crate::parser::{Parsed, RequestParser};
 use crate::request_body::Body;
-use crate::request_header::Header;
+pub use crate::request_header::Header;
 use crate::request_line::RequestLine;
+use crate::utils::{parse_header_params, percent_decode};
 use json::{object, JsonValue};
+use serde::de::DeserializeOwned;
+use std::cell::OnceCell;
 use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::str::Utf8Error;
+pub use uuid::Uuid;
 
 #[derive(Debug)]
 pub struct Request {
     pub query: JsonValue,
     pub request_line: RequestLine,
     pub headers: Header,
-    pub body: Body,
+    trailers: Header,
+    content: Vec<u8>,
+    body: OnceCell<(Body, Vec<FilePart>)>,
+    reader: Option<BodyReader>,
+    peer_addr: Option<SocketAddr>,
+    forwarding: Forwarding,
+    params: Vec<(String, String)>,
 }
 impl Request {
-    pub fn parse(stream: &[u8]) -> Self {
-        let temp = String::from_utf8_lossy(stream).to_string();
+    pub fn parse(stream: &[u8]) -> Result<Self, ParseError> {
+        let mut parser = RequestParser::new();
 
-        let mut request_str = temp.split("\r\n\r\n");
+        parser.feed(stream);
 
-        let mut request_headers = request_str.next().unwrap().split("\r\n");
-
-        let request_line = RequestLine::parse(request_headers.next().unwrap());
+        loop {
+            match parser.parse()? {
+                Parsed::Complete(request) => return Ok(*request),
+                Parsed::Head => continue,
+                Parsed::Partial => return Err(ParseError::Incomplete),
+            }
+        }
+    }
 
+    pub(crate) fn new(request_line: RequestLine, headers: Header, content: Vec<u8>) -> Self {
         let query = request_line.query.clone();
 
-        let headers: Header = request_headers.into();
+        Self {
+            query,
+            request_line,
+            headers,
+            trailers: Header::new(),
+            content,
+            body: OnceCell::new(),
+            reader: None,
+            peer_addr: None,
+            forwarding: Forwarding::default(),
+            params: vec![],
+        }
+    }
 
-        let default_content_type = String::from("application/x-www-form-urlencoded");
+    /// The fields sent after a chunked body. They are kept apart from
+    /// `headers` as they arrive after the head has been checked.
+    pub fn trailers(&self) -> &Header {
+        &self.trailers
+    }
 
-        let content_type = headers["Content-Type"]
-            .as_str()
-            .unwrap_or(&default_content_type);
+    /// Stores the segments captured by the route pattern, percent-decoded.
+    pub fn set_params(&mut self, params: Vec<(String, String)>) {
+        self.params = params
+            .into_iter()
+            .map(|(name, value)| (name, percent_decode(&value)))
+            .collect();
+    }
 
-        let body_str = request_str.next().unwrap();
+    /// The decoded value of a path parameter, `None` when the route has no
+    /// such parameter or the value does not parse as `T`.
+    pub fn param<'a, T: FromParam<'a>>(&'a self, name: &str) -> Option<T> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .and_then(|(_, value)| T::from_param(value))
+    }
+
+    /// Records the address the request came from, forwarding headers are
+    /// only believed when it belongs to one of the `trusted` proxies.
+    pub fn set_peer_addr(&mut self, peer_addr: SocketAddr, trusted: &TrustedProxies) {
+        self.peer_addr = Some(peer_addr);
+        self.forwarding = forwarded::resolve(peer_addr.ip(), &self.headers, trusted);
+    }
+
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    /// The address of the client, `None` when a trusted proxy could not
+    /// tell it.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.forwarding.ip
+    }
 
-        let body = match content_type {
-            "application/x-www-form-urlencoded" if !body_str.is_empty() => Body::parse(body_str)
-                .unwrap_or(Body {
-                    content: object! {},
-                }),
-            "application/json" if !body_str.is_empty() => {
-                Body::parse_json(body_str).unwrap_or(Body {
-                    content: object! {},
-                })
+    pub fn scheme(&self) -> &str {
+        self.forwarding
+            .proto
+            .as_deref()
+            .or(self.request_line.scheme.as_deref())
+            .unwrap_or("http")
+    }
+
+    pub fn take_reader(&mut self) -> Option<BodyReader> {
+        self.reader.take()
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.content
+    }
+
+    pub fn text(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(&self.content)
+    }
+
+    pub fn body(&self) -> &Body {
+        &self.interpret_body().0
+    }
+
+    fn interpret_body(&self) -> &(Body, Vec<FilePart>) {
+        self.body.get_or_init(|| {
+            let empty = || Body {
+                content: object! {},
+            };
+
+            if self.content.is_empty() {
+                return (empty(), vec![]);
             }
-            _ if !body_str.is_empty() => {
-                log::error!("Error: wrong `Content-Type`");
-                Body {
-                    content: object! {},
+
+            let (content_type, params) = parse_header_params(
+                self.headers
+                    .get("Content-Type")
+                    .unwrap_or("application/x-www-form-urlencoded"),
+            );
+
+            let body_str = String::from_utf8_lossy(&self.content);
+
+            match content_type.to_ascii_lowercase().as_str() {
+                "application/x-www-form-urlencoded" => {
+                    (Body::parse(&body_str).unwrap_or_else(|_| empty()), vec![])
+                }
+                "application/json" => (
+                    Body::parse_json(&body_str).unwrap_or_else(|_| empty()),
+                    vec![],
+                ),
+                "multipart/form-data" => {
+                    let boundary = params
+                        .iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+                        .map(|(_, value)| value.as_str())
+                        .unwrap_or_default();
+
+                    match Multipart::parse(&self.content, boundary) {
+                        Ok(multipart) => (
+                            Body {
+                                content: multipart.fields,
+                            },
+                            multipart.files,
+                        ),
+                        Err(e) => {
+                            log::error!("Error: {}", e);
+                            (empty(), vec![])
+                        }
+                    }
+                }
+                content_type => {
+                    log::debug!("`{}` body is only available as bytes", content_type);
+                    (empty(), vec![])
                 }
             }
-            _ => Body {
-                content: object! {},
-            },
-        };
+        })
+    }
 
-        Self {
-            query,
-            request_line,
-            headers,
-            body,
+    /// The host the request is directed at. A host forwarded by a trusted
+    /// proxy comes first, then the authority of an absolute-form target and
+    /// the `Host` field.
+    pub fn host(&self) -> Option<&str> {
+        self.forwarding
+            .host
+            .as_deref()
+            .or(self.request_line.host.as_deref())
+            .or_else(|| self.headers.get("Host"))
+            .filter(|host| !host.is_empty())
+    }
+
+    pub fn keep_alive(&self) -> bool {
+        let connection = self
+            .headers
+            .get_all("Connection")
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|option| option.trim().to_ascii_lowercase())
+            .collect::<Vec<String>>();
+
+        if connection.iter().any(|option| option == "close") {
+            return false;
         }
+
+        self.request_line.http_version != "HTTP/1.0"
+            || connection.iter().any(|option| option == "keep-alive")
+    }
+
+    pub fn query_as<T: DeserializeOwned>(&self) -> Result<T, ExtractError> {
+        extract::from_value(&self.query)
+    }
+
+    pub fn body_as<T: DeserializeOwned>(&self) -> Result<T, ExtractError> {
+        extract::from_value(self.body())
+    }
+
+    pub fn cookies(&self) -> Cookies {
+        Cookies::parse(self.headers.get_all("Cookie"))
+    }
+
+    pub fn authorization(&self) -> Option<Authorization> {
+        self.headers
+            .get("Authorization")
+            .and_then(Authorization::parse)
+    }
+
+    pub fn accept(&self) -> Vec<Preference> {
+        Preference::parse_all(self.headers.get_all("Accept"))
+    }
+
+    pub fn accept_language(&self) -> Vec<Preference> {
+        Preference::parse_all(self.headers.get_all("Accept-Language"))
+    }
+
+    pub fn accept_encoding(&self) -> Vec<Preference> {
+        Preference::parse_all(self.headers.get_all("Accept-Encoding"))
+    }
+
+    /// Picks the media type the client prefers out of `available`, the
+    /// first one when the client sent no `Accept`. `None` means none of
+    /// them is acceptable.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        match self.headers.contains("Accept") {
+            true => negotiate::best(&self.accept(), available, negotiate::media_type_specificity),
+            false => available.first().copied(),
+        }
+    }
+
+    pub fn negotiate_language<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        match self.headers.contains("Accept-Language") {
+            true => negotiate::best(
+                &self.accept_language(),
+                available,
+                negotiate::language_specificity,
+            ),
+            false => available.first().copied(),
+        }
+    }
+
+    pub fn negotiate_encoding<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        match self.headers.contains("Accept-Encoding") {
+            true => negotiate::best(
+                &negotiate::with_identity(self.accept_encoding()),
+                available,
+                negotiate::encoding_specificity,
+            ),
+            false => available.first().copied(),
+        }
+    }
+
+    pub fn files(&self) -> &[FilePart] {
+        &self.interpret_body().1
+    }
+
+    pub fn file(&self, name: &str) -> Option<&FilePart> {
+        self.files().iter().find(|file| file.name == name)
     }
 }
 
@@ -72,7 +308,7 @@ impl Display for Request {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let request_line = self.request_line.to_string();
         let header = self.headers.to_string();
-        let body = self.body.to_string();
+        let body = self.body().to_string();
 
         write!(f, "{}", [request_line, header, body].join("\r\n"))
     }
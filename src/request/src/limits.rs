@@ -0,0 +1,26 @@
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_request_line: usize,
+    pub max_header_count: usize,
+    pub max_header_size: usize,
+    pub max_body_size: usize,
+    /// Applies to bodies handed to streaming routes instead of
+    /// `max_body_size`, unlimited by default.
+    pub max_stream_size: usize,
+    /// Caps what a streamed body with a content coding decodes to, a few
+    /// compressed bytes can expand without bound.
+    pub max_decoded_stream_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_request_line: 8 * 1024,
+            max_header_count: 100,
+            max_header_size: 8 * 1024,
+            max_body_size: 8 * 1024 * 1024,
+            max_stream_size: usize::MAX,
+            max_decoded_stream_size: 64 * 1024 * 1024,
+        }
+    }
+}
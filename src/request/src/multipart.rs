@@ -0,0 +1,169 @@
+use crate::utils::{find, insert_field, parse_header_params};
+use json::{object, JsonValue};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum MultipartError {
+    MissingBoundary,
+    MissingDelimiter,
+    MissingContentDisposition,
+}
+
+impl Display for MultipartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultipartError::MissingBoundary => write!(f, "missing multipart boundary"),
+            MultipartError::MissingDelimiter => write!(f, "missing multipart delimiter"),
+            MultipartError::MissingContentDisposition => {
+                write!(f, "multipart part without `Content-Disposition`")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FilePart {
+    pub name: String,
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Multipart {
+    pub fields: JsonValue,
+    pub files: Vec<FilePart>,
+}
+
+impl Multipart {
+    pub fn parse(body: &[u8], boundary: &str) -> Result<Self, MultipartError> {
+        if boundary.is_empty() {
+            return Err(MultipartError::MissingBoundary);
+        }
+
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let part_delimiter = [b"\r\n".as_slice(), &delimiter].concat();
+
+        let mut position =
+            find(body, &delimiter).ok_or(MultipartError::MissingDelimiter)? + delimiter.len();
+
+        let mut multipart = Self {
+            fields: object! {},
+            files: vec![],
+        };
+
+        loop {
+            let rest = &body[position..];
+
+            if rest.starts_with(b"--") {
+                break;
+            }
+
+            // transport padding may follow the delimiter before its CRLF
+            let line_end = find(rest, b"\r\n").ok_or(MultipartError::MissingDelimiter)?;
+            let part_start = position + line_end + 2;
+
+            let part_end = find(&body[part_start..], &part_delimiter)
+                .ok_or(MultipartError::MissingDelimiter)?
+                + part_start;
+
+            multipart.add_part(&body[part_start..part_end])?;
+
+            position = part_end + part_delimiter.len();
+        }
+
+        Ok(multipart)
+    }
+
+    fn add_part(&mut self, part: &[u8]) -> Result<(), MultipartError> {
+        let (head, content) = if part.starts_with(b"\r\n") {
+            (&part[0..0], &part[2..])
+        } else {
+            match find(part, b"\r\n\r\n") {
+                None => (part, &part[part.len()..]),
+                Some(end) => (&part[0..end], &part[end + 4..]),
+            }
+        };
+
+        let head = String::from_utf8_lossy(head);
+
+        let mut disposition = None;
+        let mut content_type = String::from("text/plain");
+
+        head.split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .for_each(|(key, value)| {
+                if key.trim().eq_ignore_ascii_case("Content-Disposition") {
+                    disposition = Some(parse_header_params(value));
+                } else if key.trim().eq_ignore_ascii_case("Content-Type") {
+                    content_type = value.trim().to_string();
+                }
+            });
+
+        let (_, params) = disposition.ok_or(MultipartError::MissingContentDisposition)?;
+
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+
+        let name = param("name").unwrap_or_default();
+
+        match param("filename") {
+            Some(filename) => self.files.push(FilePart {
+                name,
+                filename,
+                content_type,
+                content: content.to_vec(),
+            }),
+            None => insert_field(&mut self.fields, &name, &String::from_utf8_lossy(content)),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::multipart::{Multipart, MultipartError};
+    use json::object;
+
+    const BODY: &[u8] = b"preamble\r\n--XyZ\r\n\
+Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+hello world\r\n--XyZ\r\n\
+Content-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\n\
+Content-Type: image/png\r\n\r\n\
+\x89PNG\r\n\x00\x01\r\n--XyZ--\r\n";
+
+    #[test]
+    fn parse_multipart_fields_and_files() {
+        let multipart = Multipart::parse(BODY, "XyZ").unwrap();
+
+        assert_eq!(
+            multipart.fields.to_string(),
+            object! {title: "hello world"}.to_string()
+        );
+        assert_eq!(multipart.files.len(), 1);
+
+        let file = &multipart.files[0];
+
+        assert_eq!(file.name, "avatar");
+        assert_eq!(file.filename, "a.png");
+        assert_eq!(file.content_type, "image/png");
+        assert_eq!(file.content, b"\x89PNG\r\n\x00\x01");
+    }
+
+    #[test]
+    fn parse_multipart_without_delimiter() {
+        assert_eq!(
+            Multipart::parse(b"hello", "XyZ").unwrap_err(),
+            MultipartError::MissingDelimiter
+        );
+        assert_eq!(
+            Multipart::parse(BODY, "").unwrap_err(),
+            MultipartError::MissingBoundary
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::utils::parse_header_params;
+
+/// One entry of an `Accept`, `Accept-Language` or `Accept-Encoding` field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preference {
+    pub value: String,
+    pub params: Vec<(String, String)>,
+    pub quality: f32,
+}
+
+impl Preference {
+    /// Parses all entries of the given fields, ranked by quality. Entries
+    /// of the same quality keep the order they were sent in, entries with
+    /// a malformed q-value are dropped.
+    pub fn parse_all<'a>(headers: impl IntoIterator<Item = &'a str>) -> Vec<Preference> {
+        let mut preferences = headers
+            .into_iter()
+            .flat_map(|header| header.split(','))
+            .filter(|entry| !entry.trim().is_empty())
+            .filter_map(|entry| {
+                let (value, params) = parse_header_params(entry);
+                let mut quality = 1.0;
+                let mut rest = vec![];
+
+                for (key, value) in params {
+                    match key.eq_ignore_ascii_case("q") {
+                        true => quality = parse_quality(&value)?,
+                        false => rest.push((key.to_ascii_lowercase(), value)),
+                    }
+                }
+
+                Some(Preference {
+                    value: value.to_ascii_lowercase(),
+                    params: rest,
+                    quality,
+                })
+            })
+            .collect::<Vec<Preference>>();
+
+        preferences.sort_by(|a, b| b.quality.total_cmp(&a.quality));
+
+        preferences
+    }
+}
+
+fn parse_quality(value: &str) -> Option<f32> {
+    let valid = value.len() <= 5
+        && value.starts_with(['0', '1'])
+        && value.bytes().all(|b| b.is_ascii_digit() || b == b'.');
+
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|quality| valid && (0.0..=1.0).contains(quality))
+}
+
+/// Picks the candidate the client prefers most, earlier candidates win
+/// ties. Each candidate is weighed by the most specific entry matching it.
+pub(crate) fn best<'a>(
+    preferences: &[Preference],
+    candidates: &[&'a str],
+    specificity: impl Fn(&Preference, &str) -> Option<usize>,
+) -> Option<&'a str> {
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            preferences
+                .iter()
+                .filter_map(|preference| {
+                    specificity(preference, candidate).map(|s| (s, preference.quality))
+                })
+                .fold(None, |best: Option<(usize, f32)>, current| match best {
+                    Some(best) if best.0 >= current.0 => Some(best),
+                    _ => Some(current),
+                })
+                .map(|(_, quality)| (*candidate, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .fold(None, |best: Option<(&str, f32)>, current| match best {
+            Some(best) if best.1 >= current.1 => Some(best),
+            _ => Some(current),
+        })
+        .map(|(candidate, _)| candidate)
+}
+
+pub(crate) fn media_type_specificity(range: &Preference, media_type: &str) -> Option<usize> {
+    let (media_type, params) = parse_header_params(media_type);
+    let (kind, subtype) = media_type.split_once('/')?;
+    let (range_kind, range_subtype) = range.value.split_once('/')?;
+
+    let all_params = range.params.iter().all(|(key, value)| {
+        params
+            .iter()
+            .any(|(k, v)| k.eq_ignore_ascii_case(key) && v == value)
+    });
+
+    match (range_kind, range_subtype) {
+        ("*", "*") => Some(1),
+        (range_kind, "*") if range_kind.eq_ignore_ascii_case(kind) => Some(2),
+        (range_kind, range_subtype)
+            if range_kind.eq_ignore_ascii_case(kind)
+                && range_subtype.eq_ignore_ascii_case(subtype)
+                && all_params =>
+        {
+            Some(3 + range.params.len())
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn language_specificity(range: &Preference, tag: &str) -> Option<usize> {
+    let tag = tag.to_ascii_lowercase();
+
+    match range.value.as_str() {
+        "*" => Some(1),
+        range if tag == range || tag.starts_with(&format!("{}-", range)) => Some(2 + range.len()),
+        _ => None,
+    }
+}
+
+pub(crate) fn encoding_specificity(range: &Preference, coding: &str) -> Option<usize> {
+    match range.value.as_str() {
+        "*" => Some(1),
+        range if range.eq_ignore_ascii_case(coding) => Some(2),
+        _ => None,
+    }
+}
+
+/// `identity` stays acceptable unless the client excludes it, directly
+/// or through `*`.
+pub(crate) fn with_identity(mut preferences: Vec<Preference>) -> Vec<Preference> {
+    if !preferences
+        .iter()
+        .any(|preference| preference.value == "identity" || preference.value == "*")
+    {
+        preferences.push(Preference {
+            value: "identity".to_string(),
+            params: vec![],
+            quality: 1.0,
+        });
+    }
+
+    preferences
+}
+
+#[cfg(test)]
+mod test {
+    use crate::negotiate::{
+        best, encoding_specificity, language_specificity, media_type_specificity, with_identity,
+        Preference,
+    };
+
+    #[test]
+    fn parse_ranked_preferences() {
+        let preferences = Preference::parse_all([
+            "text/html;level=1, application/json;q=0.9",
+            "*/*;q=0.1, text/plain;q=0.9, image/png;q=2",
+        ]);
+
+        let values = preferences
+            .iter()
+            .map(|preference| (preference.value.as_str(), preference.quality))
+            .collect::<Vec<(&str, f32)>>();
+
+        assert_eq!(
+            values,
+            vec![
+                ("text/html", 1.0),
+                ("application/json", 0.9),
+                ("text/plain", 0.9),
+                ("*/*", 0.1)
+            ]
+        );
+        assert_eq!(
+            preferences[0].params,
+            vec![("level".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn negotiate_media_type() {
+        let preferences = Preference::parse_all(["text/*;q=0.5, application/json, */*;q=0.1"]);
+
+        assert_eq!(
+            best(
+                &preferences,
+                &["text/html", "application/json"],
+                media_type_specificity
+            ),
+            Some("application/json")
+        );
+        assert_eq!(
+            best(
+                &preferences,
+                &["image/png", "text/html"],
+                media_type_specificity
+            ),
+            Some("text/html")
+        );
+
+        let preferences = Preference::parse_all(["text/html;q=0, */*"]);
+
+        assert_eq!(
+            best(&preferences, &["text/html"], media_type_specificity),
+            None
+        );
+    }
+
+    #[test]
+    fn negotiate_language_and_encoding() {
+        let preferences = Preference::parse_all(["fr;q=0.8, en"]);
+
+        assert_eq!(
+            best(&preferences, &["fr-FR", "en-US"], language_specificity),
+            Some("en-US")
+        );
+
+        let preferences = with_identity(Preference::parse_all(["gzip;q=0.5, br"]));
+
+        assert_eq!(
+            best(&preferences, &["identity", "gzip"], encoding_specificity),
+            Some("identity")
+        );
+
+        let preferences = with_identity(Preference::parse_all(["gzip, *;q=0"]));
+
+        assert_eq!(
+            best(&preferences, &["identity", "deflate"], encoding_specificity),
+            None
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use uuid::Uuid;
+
+/// Types a path parameter can be read as through `Request::param`.
+pub trait FromParam<'a>: Sized {
+    fn from_param(value: &'a str) -> Option<Self>;
+}
+
+impl<'a> FromParam<'a> for &'a str {
+    fn from_param(value: &'a str) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl FromParam<'_> for String {
+    fn from_param(value: &str) -> Option<Self> {
+        Some(value.to_string())
+    }
+}
+
+// Only the hyphenated form is accepted, the one the `{id:uuid}` route
+// constraint matches.
+impl FromParam<'_> for Uuid {
+    fn from_param(value: &str) -> Option<Self> {
+        match value.len() {
+            36 => Uuid::parse_str(value).ok(),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! from_str_param {
+    ($($ty:ty),*) => {
+        $(
+            impl FromParam<'_> for $ty {
+                fn from_param(value: &str) -> Option<Self> {
+                    value.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+// `parse` takes a leading `+`, digits only keeps these in line with the
+// `{id:u64}` route constraint.
+macro_rules! from_digits_param {
+    ($($ty:ty),*) => {
+        $(
+            impl FromParam<'_> for $ty {
+                fn from_param(value: &str) -> Option<Self> {
+                    match value.bytes().all(|b| b.is_ascii_digit()) {
+                        true => value.parse().ok(),
+                        false => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_digits_param!(u8, u16, u32, u64, u128, usize);
+from_str_param!(i8, i16, i32, i64, i128, isize, f32, f64, bool);
+
+#[cfg(test)]
+mod test {
+    use crate::param::FromParam;
+    use uuid::Uuid;
+
+    #[test]
+    fn parse_params() {
+        assert_eq!(u64::from_param("42"), Some(42));
+        assert_eq!(u64::from_param("-1"), None);
+        assert_eq!(u64::from_param("+42"), None);
+        assert_eq!(u8::from_param(""), None);
+        assert_eq!(i32::from_param("-1"), Some(-1));
+        assert_eq!(<&str>::from_param("a b"), Some("a b"));
+        assert_eq!(
+            Uuid::from_param("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            Some(Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8))
+        );
+        assert_eq!(Uuid::from_param("67e55044"), None);
+        assert_eq!(Uuid::from_param("67e5504410b1426f9247bb680e5fe0c8"), None);
+        assert_eq!(
+            Uuid::from_param("{67e55044-10b1-426f-9247-bb680e5fe0c8}"),
+            None
+        );
+    }
+}
@@ -0,0 +1,758 @@
+use crate::body_reader::{BodyReader, LengthReader};
+use crate::chunked::{ChunkedDecoder, ChunkedReader};
+use crate::encoding::{self, Coding};
+use crate::error::ParseError;
+use crate::limits::Limits;
+use crate::request_header::Header;
+use crate::request_line::{is_authority, RequestLine};
+use crate::utils::find;
+use crate::Request;
+use std::io::{BufReader, Cursor, Read};
+
+const CRLF: &[u8] = b"\r\n";
+
+#[derive(Debug)]
+pub enum Parsed {
+    Partial,
+    Head,
+    Complete(Box<Request>),
+}
+
+#[derive(Debug)]
+enum Framing {
+    Length(usize),
+    Chunked(ChunkedDecoder),
+}
+
+#[derive(Debug)]
+enum State {
+    Head,
+    Body {
+        request_line: Box<RequestLine>,
+        headers: Header,
+        framing: Framing,
+        codings: Vec<Coding>,
+    },
+}
+
+#[derive(Debug)]
+pub struct RequestParser {
+    buffer: Vec<u8>,
+    state: State,
+    limits: Limits,
+    // the limit a route set for the current body
+    body_limit: Option<usize>,
+    // how far the head has been checked and how many of its lines are in,
+    // so every `parse` only looks at the bytes fed since
+    scanned: usize,
+    lines: usize,
+}
+
+impl Default for RequestParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestParser {
+    pub fn new() -> Self {
+        Self::with_limits(Limits::default())
+    }
+
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            buffer: vec![],
+            state: State::Head,
+            limits,
+            body_limit: None,
+            scanned: 0,
+            lines: 0,
+        }
+    }
+
+    /// Overrides the body size limit of the request whose head was just
+    /// reported through `Parsed::Head`.
+    pub fn set_body_limit(&mut self, limit: usize) {
+        self.body_limit = Some(limit);
+    }
+
+    pub fn head(&self) -> Option<(&RequestLine, &Header)> {
+        match &self.state {
+            State::Head => None,
+            State::Body {
+                request_line,
+                headers,
+                ..
+            } => Some((request_line, headers)),
+        }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    pub fn remaining(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Tries to take the next complete request out of the buffered bytes,
+    /// bytes belonging to the following pipelined requests stay buffered.
+    ///
+    /// `Parsed::Head` is reported once the request line and headers are in,
+    /// before any of the body is consumed.
+    pub fn parse(&mut self) -> Result<Parsed, ParseError> {
+        if let State::Head = self.state {
+            // a client may send empty lines before the request line
+            if self.lines == 0 {
+                let skip = self
+                    .buffer
+                    .chunks(2)
+                    .take_while(|line| *line == b"\r\n")
+                    .count();
+                self.buffer.drain(0..skip * 2);
+            }
+
+            let head_end = match self.scan_head()? {
+                None => return Ok(Parsed::Partial),
+                Some(position) => position,
+            };
+
+            let head = String::from_utf8_lossy(&self.buffer[0..head_end]).to_string();
+            self.buffer.drain(0..head_end + 2 * CRLF.len());
+            self.scanned = 0;
+            self.lines = 0;
+
+            let mut lines = head.split("\r\n");
+
+            let request_line = RequestLine::parse(lines.next().unwrap_or_default())?;
+            let headers: Header = lines.try_into()?;
+            check_host(&request_line, &headers)?;
+
+            let framing = framing(&headers, &self.limits)?;
+            let codings = Coding::parse_all(&headers)?;
+
+            self.state = State::Body {
+                request_line: Box::new(request_line),
+                headers,
+                framing,
+                codings,
+            };
+            self.body_limit = None;
+
+            return Ok(Parsed::Head);
+        }
+
+        let mut trailers = Header::new();
+        let body_limit = self.body_limit.unwrap_or(self.limits.max_body_size);
+
+        let body = match &mut self.state {
+            State::Body {
+                framing: Framing::Length(length),
+                ..
+            } => {
+                if *length > body_limit {
+                    return Err(ParseError::BodyTooLarge);
+                }
+
+                if self.buffer.len() < *length {
+                    return Ok(Parsed::Partial);
+                }
+
+                self.buffer.drain(0..*length).collect::<Vec<u8>>()
+            }
+            State::Body {
+                framing: Framing::Chunked(decoder),
+                ..
+            } => {
+                let used = decoder.feed(&self.buffer, body_limit)?;
+                self.buffer.drain(0..used);
+
+                if !decoder.is_done() {
+                    return Ok(Parsed::Partial);
+                }
+
+                let (content, fields) = decoder.take();
+
+                fields
+                    .iter()
+                    .for_each(|(key, value)| trailers.insert(key, value));
+
+                content
+            }
+            State::Head => unreachable!(),
+        };
+
+        match std::mem::replace(&mut self.state, State::Head) {
+            State::Body {
+                request_line,
+                mut headers,
+                codings,
+                ..
+            } => {
+                let body = encoding::decode(body, &codings, body_limit)?;
+
+                // the handler sees the decoded body only
+                if !codings.is_empty() {
+                    headers.remove("Content-Encoding");
+                }
+
+                let mut request = Request::new(*request_line, headers, body);
+                request.trailers = trailers;
+
+                Ok(Parsed::Complete(Box::new(request)))
+            }
+            State::Head => unreachable!(),
+        }
+    }
+
+    /// Hands the body of the request whose head was just reported through
+    /// `Parsed::Head` over to a reader pulling it from `source`, starting
+    /// with the bytes already buffered. The body is held to
+    /// `Limits::max_stream_size`, decoded bodies to
+    /// `Limits::max_decoded_stream_size` as well, unless `set_body_limit`
+    /// overrode them.
+    ///
+    /// The reader may consume bytes past the body, the parser must not be
+    /// used for the same connection afterwards.
+    pub fn stream_body<R: Read + Send + 'static>(
+        &mut self,
+        source: R,
+    ) -> Result<Option<Request>, ParseError> {
+        self.check_stream_length()?;
+
+        let (request_line, mut headers, framing, codings) =
+            match std::mem::replace(&mut self.state, State::Head) {
+                State::Head => return Ok(None),
+                State::Body {
+                    request_line,
+                    headers,
+                    framing,
+                    codings,
+                } => (request_line, headers, framing, codings),
+            };
+
+        let body_limit = match (self.body_limit, codings.is_empty()) {
+            (None, false) => self
+                .limits
+                .max_stream_size
+                .min(self.limits.max_decoded_stream_size),
+            _ => self.stream_limit(),
+        };
+        let source = Cursor::new(std::mem::take(&mut self.buffer)).chain(source);
+
+        let reader = match framing {
+            Framing::Length(length) => BodyReader::new(
+                encoding::decoder(LengthReader::new(source, length), &codings),
+                body_limit,
+            ),
+            Framing::Chunked(_) => BodyReader::new(
+                encoding::decoder(ChunkedReader::new(BufReader::new(source)), &codings),
+                body_limit,
+            ),
+        };
+
+        if !codings.is_empty() {
+            headers.remove("Content-Encoding");
+        }
+
+        let mut request = Request::new(*request_line, headers, vec![]);
+        request.reader = Some(reader);
+
+        Ok(Some(request))
+    }
+
+    /// Fails when the declared length of the body about to be streamed is
+    /// over the limit, so the client is not asked to send it in vain.
+    pub fn check_stream_length(&self) -> Result<(), ParseError> {
+        match self.state {
+            State::Body {
+                framing: Framing::Length(length),
+                ..
+            } if length > self.stream_limit() => Err(ParseError::BodyTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    fn stream_limit(&self) -> usize {
+        self.body_limit.unwrap_or(self.limits.max_stream_size)
+    }
+
+    // Checks the head lines fed since the last call against the limits,
+    // returns where the head ends once its empty line is in.
+    fn scan_head(&mut self) -> Result<Option<usize>, ParseError> {
+        loop {
+            let rest = &self.buffer[self.scanned..];
+            let end = find(rest, CRLF);
+            let length = end.unwrap_or(rest.len());
+
+            if self.lines == 0 && length > self.limits.max_request_line {
+                return Err(ParseError::UriTooLong);
+            }
+
+            if self.lines > 0
+                && end != Some(0)
+                && (self.lines > self.limits.max_header_count
+                    || length > self.limits.max_header_size)
+            {
+                return Err(ParseError::HeadersTooLarge);
+            }
+
+            match end {
+                // an incomplete line is looked at again, its length is
+                // bounded by the limits checked above
+                None => return Ok(None),
+                Some(0) if self.lines > 0 => return Ok(Some(self.scanned - CRLF.len())),
+                Some(end) => {
+                    self.scanned += end + CRLF.len();
+                    self.lines += 1;
+                }
+            }
+        }
+    }
+}
+
+// HTTP/1.1 requests carry exactly one `Host` field, it may only be empty
+// when the target has no authority.
+fn check_host(request_line: &RequestLine, headers: &Header) -> Result<(), ParseError> {
+    match headers.get_all("Host")[..] {
+        [] if request_line.http_version == "HTTP/1.0" => Ok(()),
+        [] => Err(ParseError::MissingHost),
+        [host] if is_authority(host) => Ok(()),
+        [""] if request_line.host.is_none() => Ok(()),
+        _ => Err(ParseError::InvalidHost),
+    }
+}
+
+fn framing(headers: &Header, limits: &Limits) -> Result<Framing, ParseError> {
+    let content_length = content_length(headers)?;
+
+    if !headers.contains("Transfer-Encoding") {
+        return Ok(Framing::Length(content_length.unwrap_or(0)));
+    }
+
+    // a message carrying both framings is a classic request smuggling vector
+    if content_length.is_some() {
+        return Err(ParseError::BadHeader);
+    }
+
+    check_transfer_coding(headers)?;
+
+    Ok(Framing::Chunked(ChunkedDecoder::new(
+        limits.max_header_count,
+        limits.max_header_size,
+    )))
+}
+
+fn content_length(headers: &Header) -> Result<Option<usize>, ParseError> {
+    let mut lengths = headers
+        .get_all("Content-Length")
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim())
+        .map(|value| match value.bytes().all(|b| b.is_ascii_digit()) {
+            true => value.parse::<usize>().ok(),
+            false => None,
+        })
+        .collect::<Option<Vec<usize>>>()
+        .ok_or(ParseError::InvalidContentLength)?;
+
+    lengths.dedup();
+
+    match lengths[..] {
+        [] => Ok(None),
+        [length] => Ok(Some(length)),
+        _ => Err(ParseError::InvalidContentLength),
+    }
+}
+
+// Only a lone `chunked` is understood, anything layered on top of it would
+// have to be decoded before the body could be framed.
+fn check_transfer_coding(headers: &Header) -> Result<(), ParseError> {
+    let codings = headers
+        .get_all("Transfer-Encoding")
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|coding| coding.trim())
+        .collect::<Vec<&str>>();
+
+    if codings.iter().any(|coding| coding.is_empty()) {
+        return Err(ParseError::BadHeader);
+    }
+
+    match codings
+        .iter()
+        .find(|coding| !coding.eq_ignore_ascii_case("chunked"))
+    {
+        Some(coding) => Err(ParseError::UnsupportedTransferCoding(coding.to_string())),
+        None if codings.len() == 1 => Ok(()),
+        // `chunked` must not be applied more than once
+        None => Err(ParseError::BadHeader),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::{Parsed, RequestParser};
+    use crate::{BodyTooLarge, Limits, ParseError};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::{Cursor, Read, Write};
+
+    fn complete(parser: &mut RequestParser) -> crate::Request {
+        loop {
+            match parser.parse().unwrap() {
+                Parsed::Complete(request) => return *request,
+                Parsed::Head => continue,
+                Parsed::Partial => panic!("request should be complete"),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_request_fed_byte_by_byte() {
+        let data = b"POST /echo HTTP/1.1\r\nHost: localhost\r\nContent-Length: 7\r\n\r\ntest=12";
+        let mut parser = RequestParser::new();
+
+        for byte in &data[0..data.len() - 1] {
+            parser.feed(&[*byte]);
+            assert!(!matches!(parser.parse().unwrap(), Parsed::Complete(_)));
+        }
+
+        parser.feed(&data[data.len() - 1..]);
+
+        let request = complete(&mut parser);
+
+        assert_eq!(request.request_line.url, "/echo");
+        assert_eq!(request.host(), Some("localhost"));
+        assert_eq!(request.body()["test"], "12");
+        assert!(parser.remaining().is_empty());
+    }
+
+    #[test]
+    fn parse_pipelined_requests_in_order() {
+        let mut parser = RequestParser::new();
+
+        parser.feed(
+            b"GET /a HTTP/1.1\r\nHost: localhost\r\n\r\n\
+POST /b HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nx=1\r\n0\r\n\r\n\
+GET /c HTTP/1.1\r\nHost: localhost\r\n",
+        );
+
+        assert_eq!(complete(&mut parser).request_line.url, "/a");
+
+        let second = complete(&mut parser);
+        assert_eq!(second.request_line.url, "/b");
+        assert_eq!(second.body()["x"], "1");
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Partial));
+        assert_eq!(
+            parser.remaining(),
+            b"GET /c HTTP/1.1\r\nHost: localhost\r\n"
+        );
+
+        parser.feed(b"\r\n");
+        assert_eq!(complete(&mut parser).request_line.url, "/c");
+        assert!(parser.remaining().is_empty());
+    }
+
+    #[test]
+    fn keep_trailers_apart_from_headers() {
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nx=1\r\n0\r\nX-Forwarded-For: 6.6.6.6\r\nContent-Encoding: gzip\r\n\r\n");
+
+        let request = complete(&mut parser);
+
+        assert_eq!(request.body()["x"], "1");
+        assert!(!request.headers.contains("X-Forwarded-For"));
+        assert!(!request.headers.contains("Content-Encoding"));
+        assert_eq!(request.trailers().get("X-Forwarded-For"), Some("6.6.6.6"));
+    }
+
+    #[test]
+    fn parse_binary_body_unchanged() {
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST /img HTTP/1.1\r\nHost: localhost\r\nContent-Type: image/png\r\nContent-Length: 4\r\n\r\n\x89\xff\x00P");
+
+        let request = complete(&mut parser);
+
+        assert_eq!(request.bytes(), b"\x89\xff\x00P");
+        assert!(request.text().is_err());
+        assert!(request.body().is_empty());
+    }
+
+    #[test]
+    fn stream_request_body() {
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel");
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+
+        let source: &[u8] = b"lo\r\n6\r\n world\r\n0\r\n\r\n";
+        let mut request = parser.stream_body(source).unwrap().unwrap();
+        let mut body = String::new();
+
+        request
+            .take_reader()
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+
+        assert_eq!(request.request_line.url, "/upload");
+        assert_eq!(body, "hello world");
+        assert!(parser.remaining().is_empty());
+    }
+
+    #[test]
+    fn stream_past_the_buffered_limit() {
+        let limits = Limits {
+            max_body_size: 4,
+            ..Limits::default()
+        };
+        let mut parser = RequestParser::with_limits(limits);
+
+        parser.feed(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\n");
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+
+        let source: &[u8] = b"hello world";
+        let mut body = String::new();
+
+        parser
+            .stream_body(source)
+            .unwrap()
+            .unwrap()
+            .take_reader()
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+
+        assert_eq!(body, "hello world");
+
+        let mut parser = RequestParser::with_limits(Limits {
+            max_stream_size: 4,
+            ..limits
+        });
+
+        parser.feed(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\n");
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+        assert_eq!(
+            parser.stream_body(source).unwrap_err(),
+            ParseError::BodyTooLarge
+        );
+    }
+
+    #[test]
+    fn stream_gzip_body_up_to_the_decoded_limit() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(&[b'a'; 64]).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut parser = RequestParser::with_limits(Limits {
+            max_decoded_stream_size: 32,
+            ..Limits::default()
+        });
+
+        parser.feed(
+            format!(
+                "POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+        );
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+
+        let e = parser
+            .stream_body(Cursor::new(body))
+            .unwrap()
+            .unwrap()
+            .take_reader()
+            .unwrap()
+            .read_to_end(&mut vec![])
+            .unwrap_err();
+
+        assert!(BodyTooLarge::caused(&e));
+    }
+
+    #[test]
+    fn parse_gzip_body() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(br#"{"name": "value"}"#).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut parser = RequestParser::new();
+
+        parser.feed(
+            format!(
+                "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .as_bytes(),
+        );
+        parser.feed(&body);
+
+        let request = complete(&mut parser);
+
+        assert_eq!(request.body().content["name"], "value");
+        assert!(!request.headers.contains("Content-Encoding"));
+
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Encoding: br\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(
+            parser.parse().unwrap_err(),
+            ParseError::UnsupportedEncoding("br".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_host() {
+        let cases: [(&[u8], Option<ParseError>); 6] = [
+            (b"GET / HTTP/1.1\r\n\r\n", Some(ParseError::MissingHost)),
+            (b"GET / HTTP/1.0\r\n\r\n", None),
+            (
+                b"GET / HTTP/1.1\r\nHost: a.com\r\nHost: b.com\r\n\r\n",
+                Some(ParseError::InvalidHost),
+            ),
+            (
+                b"GET / HTTP/1.1\r\nHost: a.com/x\r\n\r\n",
+                Some(ParseError::InvalidHost),
+            ),
+            (b"GET / HTTP/1.1\r\nHost:\r\n\r\n", None),
+            (b"GET http://a.com/ HTTP/1.1\r\nHost: b.com\r\n\r\n", None),
+        ];
+
+        for (data, error) in cases {
+            let mut parser = RequestParser::new();
+
+            parser.feed(data);
+
+            assert_eq!(parser.parse().err(), error);
+        }
+    }
+
+    #[test]
+    fn parse_ambiguous_framing() {
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n");
+        assert_eq!(parser.parse().unwrap_err(), ParseError::BadHeader);
+
+        let mut parser = RequestParser::new();
+
+        parser.feed(b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3, 4\r\n\r\n");
+        assert_eq!(
+            parser.parse().unwrap_err(),
+            ParseError::InvalidContentLength
+        );
+    }
+
+    #[test]
+    fn parse_transfer_codings() {
+        let cases: [(&[u8], ParseError); 4] = [
+            (
+                b"Transfer-Encoding: gzip, chunked",
+                ParseError::UnsupportedTransferCoding("gzip".to_string()),
+            ),
+            (
+                b"Transfer-Encoding: gzip\r\nTransfer-Encoding: chunked",
+                ParseError::UnsupportedTransferCoding("gzip".to_string()),
+            ),
+            (
+                b"Transfer-Encoding: chunked, chunked",
+                ParseError::BadHeader,
+            ),
+            (b"Transfer-Encoding: chunked,", ParseError::BadHeader),
+        ];
+
+        for (header, error) in cases {
+            let mut parser = RequestParser::new();
+
+            parser.feed(b"POST / HTTP/1.1\r\nHost: localhost\r\n");
+            parser.feed(header);
+            parser.feed(b"\r\n\r\n");
+            assert_eq!(parser.parse().unwrap_err(), error);
+        }
+
+        let mut parser = RequestParser::new();
+
+        parser.feed(
+            b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: Chunked\r\n\r\n0\r\n\r\n",
+        );
+        assert!(complete(&mut parser).bytes().is_empty());
+    }
+
+    #[test]
+    fn parse_with_limits() {
+        let limits = Limits {
+            max_request_line: 20,
+            max_header_count: 2,
+            max_header_size: 32,
+            max_body_size: 4,
+            max_stream_size: 4,
+            max_decoded_stream_size: 4,
+        };
+
+        let cases: [(&[u8], ParseError); 6] = [
+            (b"GET /a-very-long-target", ParseError::UriTooLong),
+            (
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nA: 1\r\nB: 2\r\nC: 3\r\n",
+                ParseError::HeadersTooLarge,
+            ),
+            (
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nX-Long: 0123456789abcdef0123456789",
+                ParseError::HeadersTooLarge,
+            ),
+            (
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\n",
+                ParseError::BodyTooLarge,
+            ),
+            (
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\n",
+                ParseError::BodyTooLarge,
+            ),
+            (
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n0\r\nA: 1\r\nB: 2\r\nC: 3\r\n",
+                ParseError::HeadersTooLarge,
+            ),
+        ];
+
+        for (data, error) in cases {
+            let mut parser = RequestParser::with_limits(limits);
+
+            parser.feed(data);
+
+            let result = loop {
+                match parser.parse() {
+                    Ok(Parsed::Head) => continue,
+                    result => break result,
+                }
+            };
+
+            assert_eq!(result.unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn parse_with_body_limit_override() {
+        let mut parser = RequestParser::with_limits(Limits {
+            max_body_size: 1,
+            ..Limits::default()
+        });
+
+        parser.feed(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\n\r\nabcGET / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 3\r\n\r\n");
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+        assert_eq!(parser.head().unwrap().0.url, "/upload");
+
+        parser.set_body_limit(3);
+        assert!(matches!(parser.parse().unwrap(), Parsed::Complete(_)));
+
+        assert!(matches!(parser.parse().unwrap(), Parsed::Head));
+        assert_eq!(parser.parse().unwrap_err(), ParseError::BodyTooLarge);
+    }
+}
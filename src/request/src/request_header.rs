@@ -1,50 +1,95 @@
-use json::{object, JsonValue};
+use crate::error::ParseError;
 use std::fmt::{Display, Formatter};
-use std::ops::{Deref, DerefMut};
 use std::str::Split;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone)]
 pub struct Header {
-    content: JsonValue,
+    entries: Vec<(String, String)>,
 }
 
-impl Deref for Header {
-    type Target = JsonValue;
+impl Header {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.content
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case(name))
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.entries.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
     }
-}
 
-impl DerefMut for Header {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.content
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 }
 
-impl From<Split<'_, &str>> for Header {
-    fn from(request_headers: Split<&str>) -> Self {
-        let mut headers = object! {};
+impl TryFrom<Split<'_, &str>> for Header {
+    type Error = ParseError;
 
-        request_headers.for_each(|header| {
-            let mut header = header.split(':');
-            let key = header.next().unwrap().trim();
-            let value = header.next().unwrap().trim();
+    fn try_from(request_headers: Split<&str>) -> Result<Self, Self::Error> {
+        let mut headers = Header::new();
 
-            headers.insert(key, value).unwrap();
-        });
+        for header in request_headers {
+            let (key, value) = header.split_once(':').ok_or(ParseError::BadHeader)?;
 
-        Self { content: headers }
+            if !is_token(key) {
+                return Err(ParseError::BadHeader);
+            }
+
+            headers.insert(key, value.trim());
+        }
+
+        Ok(headers)
     }
 }
 
+pub fn is_token(str: &str) -> bool {
+    !str.is_empty()
+        && str
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
 impl Display for Header {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}",
-            self.content
-                .entries()
+            self.iter()
                 .map(|(k, v)| { format!("{}: {}", k, v) })
                 .collect::<Vec<String>>()
                 .join("\r\n")
@@ -54,20 +99,63 @@ impl Display for Header {
 
 #[cfg(test)]
 mod test {
-    use crate::Header;
-    use json::object;
+    use crate::{Header, ParseError};
 
     #[test]
     fn parse_header() {
-        let header: Header = "Content-Length: 10\r\naccept: */*".split("\r\n").into();
+        let header: Header = "Content-Length: 10\r\naccept: */*"
+            .split("\r\n")
+            .try_into()
+            .unwrap();
 
-        assert_eq!(
-            header.content.to_string(),
-            object! {
-                "Content-Length": "10",
-                "accept": "*/*"
-            }
-            .to_string()
-        );
+        assert_eq!(header.get("Content-Length"), Some("10"));
+        assert_eq!(header.get("accept"), Some("*/*"));
+        assert_eq!(header.len(), 2);
+        assert_eq!(header.to_string(), "Content-Length: 10\r\naccept: */*");
+    }
+
+    #[test]
+    fn header_lookup_is_case_insensitive() {
+        let header: Header = "content-type: text/html".split("\r\n").try_into().unwrap();
+
+        assert_eq!(header.get("Content-Type"), Some("text/html"));
+        assert_eq!(header.get("CONTENT-TYPE"), Some("text/html"));
+        assert!(header.contains("Content-type"));
+        assert!(!header.contains("Content-Length"));
+        assert_eq!(header.get("Content-Length"), None);
+    }
+
+    #[test]
+    fn header_keeps_repeated_values_in_order() {
+        let header: Header = "Accept: text/html\r\nX-Tag: a\r\nx-tag: b\r\nX-TAG: c"
+            .split("\r\n")
+            .try_into()
+            .unwrap();
+
+        assert_eq!(header.get("X-Tag"), Some("a"));
+        assert_eq!(header.get_all("x-tag"), vec!["a", "b", "c"]);
+        assert!(header.get_all("Cookie").is_empty());
+    }
+
+    #[test]
+    fn header_value_keeps_colons() {
+        let header: Header = "Host: localhost:7878".split("\r\n").try_into().unwrap();
+
+        assert_eq!(header.get("host"), Some("localhost:7878"));
+    }
+
+    #[test]
+    fn parse_malformed_header() {
+        [
+            "Content-Length 10",
+            ": empty",
+            "Bad Name: x",
+            "Host : localhost",
+        ]
+        .iter()
+        .for_each(|header| {
+            let result: Result<Header, ParseError> = header.split("\r\n").try_into();
+            assert_eq!(result.unwrap_err(), ParseError::BadHeader)
+        });
     }
 }
@@ -1,4 +1,6 @@
-use crate::utils::parse_kv;
+use crate::error::ParseError;
+use crate::request_header::is_token;
+use crate::utils::{normalize_path, parse_kv};
 use json::{object, JsonValue};
 use methods::Methods;
 use std::fmt::{Display, Formatter};
@@ -6,45 +8,156 @@ use std::fmt::{Display, Formatter};
 #[derive(Debug)]
 pub struct RequestLine {
     pub method: Methods,
+    pub scheme: Option<String>,
+    pub host: Option<String>,
+    pub raw_target: String,
     pub url: String,
     pub http_version: String,
     pub query: JsonValue,
 }
 
 impl RequestLine {
-    pub fn parse(request_line: &str) -> Self {
-        let mut request_line = request_line.split(' ');
+    pub fn parse(request_line: &str) -> Result<Self, ParseError> {
+        let parts = request_line.split(' ').collect::<Vec<&str>>();
 
-        let method = match request_line.next().unwrap() {
-            "GET" => Methods::Get,
-            "POST" => Methods::Post,
-            _ => Methods::Get,
+        let (method, target, http_version) = match parts[..] {
+            [method, target, version] if !method.is_empty() && !target.is_empty() => {
+                (method, target, version)
+            }
+            _ => return Err(ParseError::BadRequestLine),
         };
 
-        let mut url_with_query = request_line.next().unwrap().split('?');
+        if !is_token(method) {
+            return Err(ParseError::BadRequestLine);
+        }
 
-        let url = url_with_query.next().unwrap().to_string();
+        let method = Methods::from(method);
 
-        let query = match url_with_query.next() {
-            None => {
-                object! {}
-            }
-            Some(q) => parse_kv(q),
+        let raw_target = target.to_string();
+        let (scheme, host, target) = split_target(target)?;
+
+        let (url, query) = match target.split_once('?') {
+            None => (target.as_str(), object! {}),
+            Some((url, q)) => (url, parse_kv(q)),
         };
 
-        let http_version = request_line.next().unwrap().to_string();
+        let url = match url.starts_with('/') {
+            true => normalize_path(url).ok_or(ParseError::BadRequestLine)?,
+            false => url.to_string(),
+        };
 
-        Self {
+        let http_version = parse_version(http_version)?;
+
+        Ok(Self {
             method,
+            scheme,
+            host,
+            raw_target,
             url,
             http_version,
             query,
+        })
+    }
+}
+
+// Splits absolute-form targets into scheme, authority and path, an
+// authority-form target leaves the path empty.
+fn split_target(target: &str) -> Result<(Option<String>, Option<String>, String), ParseError> {
+    if target.starts_with('/') || target == "*" {
+        return Ok((None, None, target.to_string()));
+    }
+
+    if let Some((scheme, rest)) = target.split_once("://") {
+        let end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(end);
+
+        if !is_scheme(scheme) || !is_authority(authority) {
+            return Err(ParseError::BadRequestLine);
+        }
+
+        let path = match path.starts_with('/') {
+            true => path.to_string(),
+            false => format!("/{}", path),
+        };
+
+        return Ok((
+            Some(scheme.to_ascii_lowercase()),
+            Some(authority.to_string()),
+            path,
+        ));
+    }
+
+    if target.contains(':') && is_authority(target) {
+        return Ok((None, Some(target.to_string()), String::new()));
+    }
+
+    Err(ParseError::BadRequestLine)
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+}
+
+/// Checks `host[:port]`, where host is a registered name, an IPv4 address
+/// or a bracketed IPv6 literal. User info is not accepted.
+pub(crate) fn is_authority(authority: &str) -> bool {
+    let port = match authority.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((literal, port))
+                if !literal.is_empty()
+                    && literal
+                        .bytes()
+                        .all(|b| b.is_ascii_hexdigit() || b == b':' || b == b'.') =>
+            {
+                port
+            }
+            _ => return false,
+        },
+        None => {
+            let (host, port) = authority.split_at(authority.find(':').unwrap_or(authority.len()));
+
+            if host.is_empty()
+                || !host
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=%".contains(&b))
+            {
+                return false;
+            }
+
+            port
         }
+    };
+
+    port.is_empty()
+        || port
+            .strip_prefix(':')
+            .is_some_and(|port| port.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn parse_version(version: &str) -> Result<String, ParseError> {
+    let digits = version
+        .strip_prefix("HTTP/")
+        .and_then(|v| v.split_once('.'))
+        .filter(|(major, minor)| {
+            [major, minor]
+                .iter()
+                .all(|d| d.len() == 1 && d.chars().all(|c| c.is_ascii_digit()))
+        })
+        .ok_or(ParseError::BadRequestLine)?;
+
+    match digits {
+        ("1", _) => Ok(version.to_string()),
+        _ => Err(ParseError::InvalidVersion),
     }
 }
 
-impl From<&str> for RequestLine {
-    fn from(request_line: &str) -> Self {
+impl TryFrom<&str> for RequestLine {
+    type Error = ParseError;
+
+    fn try_from(request_line: &str) -> Result<Self, Self::Error> {
         RequestLine::parse(request_line)
     }
 }
@@ -61,13 +174,13 @@ impl Display for RequestLine {
 
 #[cfg(test)]
 mod test {
-    use crate::RequestLine;
+    use crate::{ParseError, RequestLine};
     use json::object;
     use methods::Methods;
 
     #[test]
     fn parse_get_request_without_query() {
-        let req: RequestLine = "GET / HTTP/1.1".into();
+        let req: RequestLine = "GET / HTTP/1.1".try_into().unwrap();
 
         assert_eq!(req.url, "/".to_string());
         assert_eq!(req.method.to_string(), Methods::Get.to_string());
@@ -77,7 +190,7 @@ mod test {
 
     #[test]
     fn parse_get_request_with_query() {
-        let req: RequestLine = "GET /hello?name=rust&age=7 HTTP/1.1".into();
+        let req: RequestLine = "GET /hello?name=rust&age=7 HTTP/1.1".try_into().unwrap();
 
         assert_eq!(req.url, "/hello".to_string());
         assert_eq!(req.method.to_string(), Methods::Get.to_string());
@@ -87,4 +200,114 @@ mod test {
             object! {name: "rust", age: "7"}.to_string()
         )
     }
+
+    #[test]
+    fn parse_get_request_with_encoded_query() {
+        let req: RequestLine = "GET /search?q=a+b%3Fc&tag=x&tag=y HTTP/1.1"
+            .try_into()
+            .unwrap();
+
+        assert_eq!(req.url, "/search".to_string());
+        assert_eq!(
+            req.query.to_string(),
+            object! {q: "a b?c", tag: ["x", "y"]}.to_string()
+        )
+    }
+
+    #[test]
+    fn parse_methods() {
+        [
+            ("DELETE", Methods::Delete),
+            ("PATCH", Methods::Patch),
+            ("OPTIONS", Methods::Options),
+            ("PURGE", Methods::Extension("PURGE".to_string())),
+        ]
+        .into_iter()
+        .for_each(|(name, method)| {
+            let req = RequestLine::parse(&format!("{} /users HTTP/1.1", name)).unwrap();
+
+            assert_eq!(req.method, method);
+        });
+
+        assert_eq!(
+            RequestLine::parse("GE(T / HTTP/1.1").unwrap_err(),
+            ParseError::BadRequestLine
+        );
+    }
+
+    #[test]
+    fn parse_normalized_target() {
+        let req = RequestLine::parse("GET /static//css/./%61.css?v=1 HTTP/1.1").unwrap();
+
+        assert_eq!(req.url, "/static/css/a.css");
+        assert_eq!(req.raw_target, "/static//css/./%61.css?v=1");
+
+        let req = RequestLine::parse("GET http://example.com/a/../b HTTP/1.1").unwrap();
+
+        assert_eq!(req.url, "/b");
+        assert_eq!(
+            RequestLine::parse("GET /static/../../secret HTTP/1.1").unwrap_err(),
+            ParseError::BadRequestLine
+        );
+    }
+
+    #[test]
+    fn parse_absolute_form_target() {
+        let req = RequestLine::parse("GET HTTP://example.com:8080/a/b?x=1 HTTP/1.1").unwrap();
+
+        assert_eq!(req.scheme.as_deref(), Some("http"));
+        assert_eq!(req.host.as_deref(), Some("example.com:8080"));
+        assert_eq!(req.url, "/a/b");
+        assert_eq!(req.query.to_string(), object! {x: "1"}.to_string());
+
+        let req = RequestLine::parse("GET http://[::1]?x=1 HTTP/1.1").unwrap();
+
+        assert_eq!(req.host.as_deref(), Some("[::1]"));
+        assert_eq!(req.url, "/");
+    }
+
+    #[test]
+    fn parse_authority_form_target() {
+        let req = RequestLine::parse("CONNECT example.com:443 HTTP/1.1").unwrap();
+
+        assert_eq!(req.scheme, None);
+        assert_eq!(req.host.as_deref(), Some("example.com:443"));
+        assert_eq!(req.url, "");
+
+        let req = RequestLine::parse("GET /a HTTP/1.1").unwrap();
+
+        assert_eq!(req.host, None);
+    }
+
+    #[test]
+    fn parse_malformed_request_line() {
+        [
+            "",
+            "GET",
+            "GET /",
+            "GET  / HTTP/1.1",
+            "GET / HTTP/1.1 x",
+            "GET / HTTX/1.1",
+            "GET index.html HTTP/1.1",
+            "GET http://user@example.com/ HTTP/1.1",
+            "GET http:///a HTTP/1.1",
+            "GET 1http://example.com/ HTTP/1.1",
+        ]
+        .iter()
+        .for_each(|line| {
+            assert_eq!(
+                RequestLine::parse(line).unwrap_err(),
+                ParseError::BadRequestLine
+            )
+        });
+    }
+
+    #[test]
+    fn parse_unsupported_version() {
+        assert_eq!(
+            RequestLine::parse("GET / HTTP/2.0").unwrap_err(),
+            ParseError::InvalidVersion
+        );
+        assert!(RequestLine::parse("GET / HTTP/1.0").is_ok());
+    }
 }
@@ -3,22 +3,289 @@ use json::{object, JsonValue};
 pub fn parse_kv(str: &str) -> JsonValue {
     let mut obj = object! {};
 
-    if str.len() > 3 {
-        str.split('&').for_each(|kv| {
-            let mut kv = kv.split('=');
-            let key = kv.next().unwrap();
-            let value = kv.next().unwrap();
+    str.split('&').filter(|kv| !kv.is_empty()).for_each(|kv| {
+        let (key, value) = kv.split_once('=').unwrap_or((kv, ""));
 
-            obj.insert(key, value).unwrap();
-        });
-    }
+        insert_field(&mut obj, &url_decode(key), &url_decode(value));
+    });
 
     obj
 }
 
+pub fn url_decode(str: &str) -> String {
+    percent_decode(&str.replace('+', " "))
+}
+
+pub fn percent_decode(str: &str) -> String {
+    let bytes = str.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => match bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| hex_pair(hex[0], hex[1]))
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Normalizes an origin-form path: unreserved characters are decoded,
+/// duplicate slashes collapsed and dot segments resolved. `None` when the
+/// path climbs above the root.
+pub fn normalize_path(path: &str) -> Option<String> {
+    let decoded = decode_unreserved(path);
+    let parts = decoded.split('/').skip(1).collect::<Vec<&str>>();
+    let mut segments = vec![];
+
+    for (i, part) in parts.iter().enumerate() {
+        let last = i == parts.len() - 1;
+
+        match *part {
+            "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            "" if !last => continue,
+            part => {
+                segments.push(part);
+                continue;
+            }
+        }
+
+        // a trailing dot segment still names a directory
+        if last {
+            segments.push("");
+        }
+    }
+
+    Some(format!("/{}", segments.join("/")))
+}
+
+fn decode_unreserved(str: &str) -> String {
+    let bytes = str.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes
+            .get(i..i + 3)
+            .filter(|escape| escape[0] == b'%')
+            .and_then(|escape| hex_pair(escape[1], escape[2]))
+        {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            Some(byte) => {
+                decoded.extend_from_slice(format!("%{:02X}", byte).as_bytes());
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    // only ASCII was substituted, the result stays valid UTF-8
+    String::from_utf8(decoded).unwrap_or_default()
+}
+
+/// Decodes standard base64, the trailing padding is optional.
+pub fn base64_decode(str: &str) -> Option<Vec<u8>> {
+    let data = str
+        .strip_suffix("==")
+        .or(str.strip_suffix('='))
+        .unwrap_or(str);
+
+    let padded = data.len() != str.len();
+
+    if data.len() % 4 == 1 || (padded && !str.len().is_multiple_of(4)) {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for b in data.bytes() {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(decoded)
+}
+
+pub fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+pub fn hex_pair(high: u8, low: u8) -> Option<u8> {
+    let high = (high as char).to_digit(16)?;
+    let low = (low as char).to_digit(16)?;
+
+    Some((high * 16 + low) as u8)
+}
+
+pub fn insert_field(obj: &mut JsonValue, key: &str, value: &str) {
+    let keys = split_key(key);
+
+    if keys[0].is_empty() {
+        return;
+    }
+
+    insert_path(obj, &keys, value.into());
+}
+
+// Keys nested deeper than this are kept as literal keys.
+const MAX_KEY_DEPTH: usize = 32;
+
+// `user[name][first]` => ["user", "name", "first"], `tag[]` => ["tag", ""]
+fn split_key(key: &str) -> Vec<String> {
+    let literal = vec![key.to_string()];
+
+    let (root, mut rest) = match key.find('[') {
+        Some(position) if position > 0 && key.ends_with(']') => key.split_at(position),
+        _ => return literal,
+    };
+
+    let mut keys = vec![root.to_string()];
+
+    while !rest.is_empty() {
+        match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            Some((segment, remain)) if !segment.contains('[') && keys.len() <= MAX_KEY_DEPTH => {
+                keys.push(segment.to_string());
+                rest = remain;
+            }
+            _ => return literal,
+        }
+    }
+
+    keys
+}
+
+fn insert_path(target: &mut JsonValue, keys: &[String], value: JsonValue) {
+    if !target.is_object() && !target.is_array() {
+        *target = object! {};
+    }
+
+    let key = keys[0].as_str();
+    let rest = &keys[1..];
+
+    if target.is_array() {
+        if rest.is_empty() {
+            target.push(value).unwrap();
+        } else {
+            let mut child = object! {};
+            insert_path(&mut child, rest, value);
+            target.push(child).unwrap();
+        }
+        return;
+    }
+
+    if rest.is_empty() {
+        let previous = target.remove(key);
+
+        target[key] = match previous {
+            JsonValue::Null => value,
+            JsonValue::Array(mut values) => {
+                values.push(value);
+                JsonValue::Array(values)
+            }
+            previous => JsonValue::Array(vec![previous, value]),
+        };
+        return;
+    }
+
+    let child = &mut target[key];
+
+    if child.is_null() || (!rest[0].is_empty() && !child.is_object()) {
+        *child = if rest[0].is_empty() {
+            JsonValue::new_array()
+        } else {
+            object! {}
+        };
+    } else if rest[0].is_empty() && !child.is_array() {
+        *child = JsonValue::Array(vec![child.take()]);
+    }
+
+    insert_path(child, rest, value);
+}
+
+pub fn parse_header_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut segments = vec![];
+    let mut segment = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for c in value.chars() {
+        match c {
+            _ if escaped => {
+                segment.push(c);
+                escaped = false;
+            }
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => segments.push(std::mem::take(&mut segment)),
+            _ => segment.push(c),
+        }
+    }
+
+    segments.push(segment);
+
+    let mut segments = segments.into_iter();
+    let value = segments.next().unwrap_or_default().trim().to_string();
+
+    let params = segments
+        .filter_map(|param| {
+            param
+                .split_once('=')
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+
+    (value, params)
+}
+
 #[cfg(test)]
 mod test {
-    use crate::utils::parse_kv;
+    use crate::utils::{base64_decode, normalize_path, parse_header_params, parse_kv};
     use json::object;
 
     #[test]
@@ -47,4 +314,122 @@ mod test {
             object! {test1: "1", test2: "12nd", test3: "hhh"}.to_string()
         )
     }
+
+    #[test]
+    fn parse_kv_short_pair() {
+        let obj = parse_kv("a=1");
+        assert_eq!(obj.to_string(), object! {a: "1"}.to_string())
+    }
+
+    #[test]
+    fn parse_kv_percent_and_plus_decoding() {
+        let obj = parse_kv("name=hello+rust%21&%E4%BD%A0=%E5%A5%BD&bad=%zz%4");
+        assert_eq!(
+            obj.to_string(),
+            object! {name: "hello rust!", "你": "好", bad: "%zz%4"}.to_string()
+        )
+    }
+
+    #[test]
+    fn parse_kv_key_without_value() {
+        let obj = parse_kv("flag&empty=&&x=1");
+        assert_eq!(
+            obj.to_string(),
+            object! {flag: "", empty: "", x: "1"}.to_string()
+        )
+    }
+
+    #[test]
+    fn parse_kv_repeated_keys() {
+        let obj = parse_kv("tag=a&tag=b&tag=c&list[]=1&list[]=2");
+        assert_eq!(
+            obj.to_string(),
+            object! {tag: ["a", "b", "c"], list: ["1", "2"]}.to_string()
+        )
+    }
+
+    #[test]
+    fn parse_kv_nested_keys() {
+        let obj = parse_kv("user[name]=x&user[address][city]=y&odd[=1&items[][id]=7");
+        assert_eq!(
+            obj.to_string(),
+            object! {
+                user: {name: "x", address: {city: "y"}},
+                "odd[": "1",
+                items: [{id: "7"}]
+            }
+            .to_string()
+        )
+    }
+
+    #[test]
+    fn parse_kv_deeply_nested_keys() {
+        let key = format!("a{}", "[]".repeat(32));
+        let obj = parse_kv(&format!("{}=1", key));
+        assert!(obj["a"].is_array());
+
+        let key = format!("a{}", "[]".repeat(33));
+        let obj = parse_kv(&format!("{}=1", key));
+        assert_eq!(obj[key.as_str()], "1");
+
+        let key = format!("a{}", "[]".repeat(200_000));
+        let obj = parse_kv(&format!("{}=1", key));
+        assert_eq!(obj[key.as_str()], "1");
+    }
+
+    #[test]
+    fn parse_header_params_with_quoted_values() {
+        let (value, params) =
+            parse_header_params("form-data; name=\"a;b\"; filename=\"c \\\"d\\\".txt\"");
+
+        assert_eq!(value, "form-data");
+        assert_eq!(
+            params,
+            vec![
+                ("name".to_string(), "a;b".to_string()),
+                ("filename".to_string(), "c \"d\".txt".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_paths() {
+        [
+            ("/", "/"),
+            ("/a//b", "/a/b"),
+            ("/a/./b", "/a/b"),
+            ("/a/%62", "/a/b"),
+            ("/a/b/", "/a/b/"),
+            ("/a/b/..", "/a/"),
+            ("/static/../secret", "/secret"),
+            ("/a/%2e%2E/b", "/b"),
+            ("/a%2fb%20c", "/a%2Fb%20c"),
+            ("/100%", "/100%"),
+        ]
+        .iter()
+        .for_each(|(path, normalized)| {
+            assert_eq!(
+                normalize_path(path).as_deref(),
+                Some(*normalized),
+                "{}",
+                path
+            )
+        });
+
+        ["/..", "/a/../..", "/%2e%2e/secret"]
+            .iter()
+            .for_each(|path| assert_eq!(normalize_path(path), None, "{}", path));
+    }
+
+    #[test]
+    fn decode_base64() {
+        assert_eq!(base64_decode("").unwrap(), b"");
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGk/Pz4+").unwrap(), b"hi??>>");
+        assert_eq!(base64_decode("aGVsbG8==="), None);
+        assert_eq!(base64_decode("aGVsbA="), None);
+        assert_eq!(base64_decode("aGVsb"), None);
+        assert_eq!(base64_decode("aGV sbG8="), None);
+    }
 }
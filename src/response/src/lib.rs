@@ -11,8 +11,6 @@ use std::fmt::{Display, Formatter};
 use std::fs;
 use std::io::Error;
 
-const NOT_FOUND: &str = "<h1>404 NOT FOUND!</h1>";
-
 // type Headers = HashMap<String, String>;
 
 #[derive(Debug)]
@@ -31,6 +29,10 @@ impl BaseResponse {
     pub fn client_error() -> ClientErrorResponse {
         ClientErrorResponse
     }
+
+    pub fn server_error() -> ServerErrorResponse {
+        ServerErrorResponse
+    }
 }
 
 pub struct Response {
@@ -59,6 +61,8 @@ impl Response {
     }
 
     pub fn as_bytes(&mut self) -> Vec<u8> {
+        self.set_content_length();
+
         let response = self.to_string();
         let mut res = response.into_bytes();
         let content = self.content.take();
@@ -71,6 +75,31 @@ impl Response {
         self
     }
 
+    pub fn set_header(mut self, key: &str, value: &str) -> Self {
+        self.header[key] = json::JsonValue::String(value.into());
+        self
+    }
+
+    /// Drops the content but keeps the `Content-Length` it would have had,
+    /// as answers to `HEAD` requests do.
+    pub fn without_body(mut self) -> Self {
+        self.set_content_length();
+        self.content = None;
+        self
+    }
+
+    // 204 and 304 responses carry no `Content-Length` of their own
+    fn set_content_length(&mut self) {
+        if self.header.has_key("Content-Length")
+            || matches!(self.status_line.status.code(), 204 | 304)
+        {
+            return;
+        }
+
+        let length = self.content.as_ref().map(Vec::len).unwrap_or(0);
+        self.header["Content-Length"] = length.into();
+    }
+
     pub fn message(&self) -> String {
         format!("{}", self.status_line.status)
     }
@@ -78,7 +107,10 @@ impl Response {
 
 impl Display for Response {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}\r\n{}\r\n\r\n", self.status_line, self.header)
+        match self.header.is_empty() {
+            true => write!(f, "{}\r\n\r\n", self.status_line),
+            false => write!(f, "{}\r\n{}\r\n\r\n", self.status_line, self.header),
+        }
     }
 }
 
@@ -111,6 +143,10 @@ impl SuccessResponse {
 
         Response::new("HTTP/1.1", Status::ok(), header.into(), Some(content))
     }
+
+    pub fn no_content(self) -> Response {
+        Response::new("HTTP/1.1", Status::no_content(), Header::new(), None)
+    }
 }
 
 pub struct RedirectResponse;
@@ -136,19 +172,113 @@ impl RedirectResponse {
 pub struct ClientErrorResponse;
 
 impl ClientErrorResponse {
-    pub fn not_found(self) -> Response {
-        let content = NOT_FOUND.as_bytes().to_vec();
+    pub fn bad_request(self) -> Response {
+        error_page(Status::bad_request())
+    }
+
+    pub fn bad_request_with(self, message: &str) -> Response {
+        let content = message.as_bytes().to_vec();
 
         let header = object! {
-            "Content-Type": "text/html",
+            "Content-Type": "text/plain",
             "Content-Length": content.len()
         };
 
         Response::new(
             "HTTP/1.1",
-            Status::not_found(),
+            Status::bad_request(),
             header.into(),
             Some(content),
         )
     }
+
+    pub fn unauthorized(self, challenge: &str) -> Response {
+        error_page(Status::unauthorized()).set_header("WWW-Authenticate", challenge)
+    }
+
+    pub fn not_found(self) -> Response {
+        error_page(Status::not_found())
+    }
+
+    pub fn method_not_allowed(self, allow: &str) -> Response {
+        error_page(Status::method_not_allowed()).set_header("Allow", allow)
+    }
+
+    pub fn payload_too_large(self) -> Response {
+        error_page(Status::payload_too_large())
+    }
+
+    pub fn uri_too_long(self) -> Response {
+        error_page(Status::uri_too_long())
+    }
+
+    pub fn unsupported_media_type(self) -> Response {
+        error_page(Status::unsupported_media_type())
+    }
+
+    pub fn expectation_failed(self) -> Response {
+        error_page(Status::expectation_failed())
+    }
+
+    pub fn request_header_fields_too_large(self) -> Response {
+        error_page(Status::request_header_fields_too_large())
+    }
+}
+
+pub struct ServerErrorResponse;
+
+impl ServerErrorResponse {
+    pub fn internal_server_error(self) -> Response {
+        error_page(Status::internal_server_error())
+    }
+
+    pub fn not_implemented(self) -> Response {
+        error_page(Status::not_implemented())
+    }
+
+    pub fn http_version_not_supported(self) -> Response {
+        error_page(Status::http_version_not_supported())
+    }
+}
+
+fn error_page(status: Status) -> Response {
+    let content = format!("<h1>{}!</h1>", status.to_string().to_uppercase()).into_bytes();
+
+    let header = object! {
+        "Content-Type": "text/html",
+        "Content-Length": content.len()
+    };
+
+    Response::new("HTTP/1.1", status, header.into(), Some(content))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::BaseResponse;
+
+    #[test]
+    fn serialize_response_without_headers() {
+        let bytes = BaseResponse::success().no_content().as_bytes();
+
+        assert_eq!(bytes, b"HTTP/1.1 204 No Content\r\n\r\n");
+    }
+
+    #[test]
+    fn drop_body_but_keep_length() {
+        let bytes = BaseResponse::success()
+            .string("hello")
+            .without_body()
+            .as_bytes();
+        let response = String::from_utf8(bytes).unwrap();
+
+        assert!(response.contains("Content-Length: 5\r\n"));
+        assert!(response.ends_with("\r\n\r\n"));
+
+        let bytes = BaseResponse::success()
+            .no_content()
+            .without_body()
+            .as_bytes();
+
+        assert!(!String::from_utf8(bytes).unwrap().contains("Content-Length"));
+    }
 }
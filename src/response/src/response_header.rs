@@ -38,7 +38,7 @@ impl Display for Header {
                 .entries()
                 .map(|(k, v)| { format!("{}: {}", k, v) })
                 .collect::<Vec<String>>()
-                .join("\n")
+                .join("\r\n")
         )
     }
 }
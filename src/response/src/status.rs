@@ -7,6 +7,10 @@ pub struct Status {
 }
 
 impl Status {
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
     pub fn ok() -> Self {
         Self {
             code: 200,
@@ -14,6 +18,13 @@ impl Status {
         }
     }
 
+    pub fn no_content() -> Self {
+        Self {
+            code: 204,
+            message: String::from("No Content"),
+        }
+    }
+
     pub fn moved_permanently() -> Self {
         Self {
             code: 301,
@@ -56,6 +67,48 @@ impl Status {
         }
     }
 
+    pub fn method_not_allowed() -> Self {
+        Self {
+            code: 405,
+            message: String::from("Method Not Allowed"),
+        }
+    }
+
+    pub fn payload_too_large() -> Self {
+        Self {
+            code: 413,
+            message: String::from("Payload Too Large"),
+        }
+    }
+
+    pub fn uri_too_long() -> Self {
+        Self {
+            code: 414,
+            message: String::from("URI Too Long"),
+        }
+    }
+
+    pub fn unsupported_media_type() -> Self {
+        Self {
+            code: 415,
+            message: String::from("Unsupported Media Type"),
+        }
+    }
+
+    pub fn expectation_failed() -> Self {
+        Self {
+            code: 417,
+            message: String::from("Expectation Failed"),
+        }
+    }
+
+    pub fn request_header_fields_too_large() -> Self {
+        Self {
+            code: 431,
+            message: String::from("Request Header Fields Too Large"),
+        }
+    }
+
     pub fn internal_server_error() -> Self {
         Self {
             code: 500,
@@ -63,18 +116,34 @@ impl Status {
         }
     }
 
+    pub fn not_implemented() -> Self {
+        Self {
+            code: 501,
+            message: String::from("Not Implemented"),
+        }
+    }
+
     pub fn server_unavailable() -> Self {
         Self {
             code: 503,
             message: String::from("Server Unavailable"),
         }
     }
+
+    pub fn http_version_not_supported() -> Self {
+        Self {
+            code: 505,
+            message: String::from("HTTP Version Not Supported"),
+        }
+    }
 }
 
 impl From<&str> for Status {
     fn from(str: &str) -> Self {
         if str.starts_with("200") {
             Status::ok()
+        } else if str.starts_with("204") {
+            Status::no_content()
         } else if str.starts_with("400") {
             Status::bad_request()
         } else if str.starts_with("401") {
@@ -83,10 +152,26 @@ impl From<&str> for Status {
             Status::forbidden()
         } else if str.starts_with("404") {
             Status::not_found()
+        } else if str.starts_with("405") {
+            Status::method_not_allowed()
+        } else if str.starts_with("413") {
+            Status::payload_too_large()
+        } else if str.starts_with("414") {
+            Status::uri_too_long()
+        } else if str.starts_with("415") {
+            Status::unsupported_media_type()
+        } else if str.starts_with("417") {
+            Status::expectation_failed()
+        } else if str.starts_with("431") {
+            Status::request_header_fields_too_large()
         } else if str.starts_with("500") {
             Status::internal_server_error()
+        } else if str.starts_with("501") {
+            Status::not_implemented()
         } else if str.starts_with("503") {
             Status::server_unavailable()
+        } else if str.starts_with("505") {
+            Status::http_version_not_supported()
         } else {
             Status::internal_server_error()
         }
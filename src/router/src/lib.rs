@@ -0,0 +1,460 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+/// A prefix tree over path segments. Patterns are made of static segments,
+/// named `:param` or `{param}` segments and may end with a `*rest` segment
+/// capturing the remainder of the path. Named segments can be constrained
+/// as in `{id:u64}`, `{id:uuid}` or `{slug:[a-z-]+}`, the constraint is
+/// checked against the raw segment. When several fit, static segments win
+/// over constrained ones, those over unconstrained ones and those over the
+/// catch-all, the next kind is only tried if the rest of the path does not
+/// match.
+pub struct Router<T> {
+    root: Node<T>,
+    fallback: Option<T>,
+    len: usize,
+}
+
+pub struct Match<'a, T> {
+    pub value: &'a T,
+    pub params: Vec<(String, String)>,
+}
+
+struct Node<T> {
+    statics: HashMap<String, Node<T>>,
+    // tried in the order they were added
+    constrained: Vec<(String, Constraint, Node<T>)>,
+    param: Option<Box<Node<T>>>,
+    catch_all: Option<Box<Node<T>>>,
+    // the parameter names of the pattern ending here
+    route: Option<(Vec<String>, T)>,
+}
+
+enum Constraint {
+    U64,
+    Uuid,
+    Pattern(Regex),
+}
+
+enum Segment<'a> {
+    Static(&'a str),
+    Param(&'a str, Option<&'a str>),
+    CatchAll(&'a str),
+}
+
+impl<T> Default for Router<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self {
+            statics: HashMap::new(),
+            constrained: vec![],
+            param: None,
+            catch_all: None,
+            route: None,
+        }
+    }
+}
+
+impl<T> Router<T> {
+    pub fn new() -> Self {
+        Self {
+            root: Node::default(),
+            fallback: None,
+            len: 0,
+        }
+    }
+
+    /// Adds a pattern like `/users/{id:u64}/posts/:slug` or `/files/*path`,
+    /// returns the value it replaced.
+    ///
+    /// Panics when a constraint is not a valid regular expression or a
+    /// catch-all is not the last segment.
+    pub fn insert(&mut self, pattern: &str, value: T) -> Option<T> {
+        let mut node = &mut self.root;
+        let mut names = vec![];
+
+        let segments = segments(pattern).collect::<Vec<&str>>();
+
+        if segments
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|segment| matches!(parse_segment(segment), Segment::CatchAll(_)))
+        {
+            panic!("catch-all is not the last segment in {}", pattern);
+        }
+
+        for segment in segments {
+            node = match parse_segment(segment) {
+                Segment::Static(segment) => node.statics.entry(segment.to_string()).or_default(),
+                Segment::Param(name, None) => {
+                    names.push(name.to_string());
+                    node.param.get_or_insert_with(Box::default)
+                }
+                Segment::Param(name, Some(source)) => {
+                    names.push(name.to_string());
+
+                    let index = match node.constrained.iter().position(|(s, ..)| s == source) {
+                        Some(index) => index,
+                        None => {
+                            let constraint = Constraint::new(source).unwrap_or_else(|e| {
+                                panic!("invalid constraint in {}: {}", pattern, e)
+                            });
+
+                            node.constrained.push((
+                                source.to_string(),
+                                constraint,
+                                Node::default(),
+                            ));
+                            node.constrained.len() - 1
+                        }
+                    };
+
+                    &mut node.constrained[index].2
+                }
+                Segment::CatchAll(name) => {
+                    names.push(name.to_string());
+                    node.catch_all.get_or_insert_with(Box::default)
+                }
+            };
+        }
+
+        let replaced = node.route.replace((names, value)).map(|(_, value)| value);
+
+        if replaced.is_none() {
+            self.len += 1;
+        }
+
+        replaced
+    }
+
+    pub fn get(&self, path: &str) -> Option<Match<'_, T>> {
+        let segments = segments(path).collect::<Vec<&str>>();
+        let mut values = vec![];
+
+        let (names, value) = self.root.find(&segments, &mut values)?;
+
+        let params = names
+            .iter()
+            .zip(values)
+            .map(|(name, value)| (name.clone(), value))
+            .collect();
+
+        Some(Match { value, params })
+    }
+
+    /// Looks a pattern up as it was inserted, parameter names aside.
+    pub fn get_mut(&mut self, pattern: &str) -> Option<&mut T> {
+        let mut node = &mut self.root;
+
+        for segment in segments(pattern) {
+            node = match parse_segment(segment) {
+                Segment::Static(segment) => node.statics.get_mut(segment)?,
+                Segment::Param(_, None) => node.param.as_deref_mut()?,
+                Segment::Param(_, Some(source)) => node
+                    .constrained
+                    .iter_mut()
+                    .find(|(s, ..)| s == source)
+                    .map(|(.., node)| node)?,
+                Segment::CatchAll(_) => node.catch_all.as_deref_mut()?,
+            };
+        }
+
+        node.route.as_mut().map(|(_, value)| value)
+    }
+
+    /// Sets the value to use when no pattern matches, returns the one it
+    /// replaced.
+    pub fn set_fallback(&mut self, value: T) -> Option<T> {
+        self.fallback.replace(value)
+    }
+
+    pub fn fallback(&self) -> Option<&T> {
+        self.fallback.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> Node<T> {
+    fn find(&self, segments: &[&str], values: &mut Vec<String>) -> Option<&(Vec<String>, T)> {
+        let (segment, rest) = match segments.split_first() {
+            None => return self.route.as_ref(),
+            Some(split) => split,
+        };
+
+        if let Some(found) = self
+            .statics
+            .get(*segment)
+            .and_then(|node| node.find(rest, values))
+        {
+            return Some(found);
+        }
+
+        if !segment.is_empty() {
+            let params = self
+                .constrained
+                .iter()
+                .filter(|(_, constraint, _)| constraint.matches(segment))
+                .map(|(.., node)| node)
+                .chain(self.param.as_deref());
+
+            for node in params {
+                values.push(segment.to_string());
+
+                match node.find(rest, values) {
+                    None => values.pop(),
+                    found => return found,
+                };
+            }
+        }
+
+        let route = self.catch_all.as_ref()?.route.as_ref()?;
+
+        values.push(segments.join("/"));
+
+        Some(route)
+    }
+}
+
+impl Constraint {
+    fn new(source: &str) -> Result<Self, regex::Error> {
+        match source {
+            "u64" => Ok(Constraint::U64),
+            "uuid" => Ok(Constraint::Uuid),
+            pattern => Regex::new(&format!("^(?:{})$", pattern)).map(Constraint::Pattern),
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            // `parse` alone would let `+42` through
+            Constraint::U64 => {
+                value.bytes().all(|b| b.is_ascii_digit()) && value.parse::<u64>().is_ok()
+            }
+            Constraint::Uuid => is_uuid(value),
+            Constraint::Pattern(regex) => regex.is_match(value),
+        }
+    }
+}
+
+// The hyphenated form, `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+fn is_uuid(value: &str) -> bool {
+    value.len() == 36
+        && value.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+fn parse_segment(segment: &str) -> Segment<'_> {
+    if let Some(name) = segment.strip_prefix('*') {
+        return Segment::CatchAll(name);
+    }
+
+    if let Some(name) = segment.strip_prefix(':') {
+        return Segment::Param(name, None);
+    }
+
+    match segment
+        .strip_prefix('{')
+        .and_then(|inner| inner.strip_suffix('}'))
+    {
+        Some(inner) => match inner.split_once(':') {
+            Some((name, constraint)) => Segment::Param(name, Some(constraint)),
+            None => Segment::Param(inner, None),
+        },
+        None => Segment::Static(segment),
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').skip(1)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Router;
+
+    fn lookup(router: &Router<u8>, path: &str) -> Option<(u8, Vec<(String, String)>)> {
+        router.get(path).map(|found| (*found.value, found.params))
+    }
+
+    fn param(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn match_static_and_named_segments() {
+        let mut router = Router::new();
+
+        router.insert("/", 0);
+        router.insert("/users", 1);
+        router.insert("/users/:id", 2);
+        router.insert("/users/:id/posts/:post_id", 3);
+
+        assert_eq!(lookup(&router, "/"), Some((0, vec![])));
+        assert_eq!(lookup(&router, "/users"), Some((1, vec![])));
+        assert_eq!(
+            lookup(&router, "/users/7"),
+            Some((2, vec![param("id", "7")]))
+        );
+        assert_eq!(
+            lookup(&router, "/users/7/posts/42"),
+            Some((3, vec![param("id", "7"), param("post_id", "42")]))
+        );
+        assert_eq!(lookup(&router, "/users/"), None);
+        assert_eq!(lookup(&router, "/users/7/posts"), None);
+        assert_eq!(router.len(), 4);
+    }
+
+    #[test]
+    fn prefer_static_segments() {
+        let mut router = Router::new();
+
+        router.insert("/users/:id", 1);
+        router.insert("/users/me", 2);
+        router.insert("/users/:id/posts", 3);
+        router.insert("/users/me/settings", 4);
+
+        assert_eq!(router.get("/users/me").unwrap().value, &2);
+        assert_eq!(router.get("/users/you").unwrap().value, &1);
+        assert_eq!(router.get("/users/me/settings").unwrap().value, &4);
+
+        // falls back to the named segment when the static branch dead-ends
+        let found = router.get("/users/me/posts").unwrap();
+
+        assert_eq!(found.value, &3);
+        assert_eq!(found.params, vec![param("id", "me")]);
+    }
+
+    #[test]
+    fn replace_and_look_up_patterns() {
+        let mut router = Router::new();
+
+        assert_eq!(router.insert("/users/:id", 1), None);
+        assert_eq!(router.insert("/users/:user", 2), Some(1));
+
+        *router.get_mut("/users/:any").unwrap() += 1;
+
+        let found = router.get("/users/7").unwrap();
+
+        assert_eq!(found.value, &3);
+        assert_eq!(found.params, vec![param("user", "7")]);
+        assert!(router.get_mut("/users").is_none());
+        assert_eq!(router.len(), 1);
+
+        assert_eq!(router.fallback(), None);
+        assert_eq!(router.set_fallback(0), None);
+        assert_eq!(router.fallback(), Some(&0));
+        assert!(router.get("/posts").is_none());
+    }
+
+    #[test]
+    fn capture_the_remaining_path() {
+        let mut router = Router::new();
+
+        router.insert("/files/*path", 1);
+        router.insert("/files/:name/meta", 2);
+        router.insert("/files/readme", 3);
+        router.insert("/*rest", 4);
+
+        assert_eq!(
+            lookup(&router, "/files/a/b/c.txt"),
+            Some((1, vec![param("path", "a/b/c.txt")]))
+        );
+        assert_eq!(
+            lookup(&router, "/files/"),
+            Some((1, vec![param("path", "")]))
+        );
+        assert_eq!(
+            lookup(&router, "/files/a/meta"),
+            Some((2, vec![param("name", "a")]))
+        );
+        assert_eq!(lookup(&router, "/files/readme"), Some((3, vec![])));
+        assert_eq!(
+            lookup(&router, "/files"),
+            Some((4, vec![param("rest", "files")]))
+        );
+        assert_eq!(lookup(&router, "/"), Some((4, vec![param("rest", "")])));
+
+        *router.get_mut("/files/*any").unwrap() += 10;
+
+        assert_eq!(router.get("/files/x/y").unwrap().value, &11);
+    }
+
+    #[test]
+    fn check_constraints() {
+        let mut router = Router::new();
+
+        router.insert("/users/{id:u64}", 1);
+        router.insert("/users/{id:uuid}", 2);
+        router.insert("/users/{name}", 3);
+        router.insert("/posts/{slug:[a-z-]+}", 4);
+        router.insert(r"/posts/{year:\d{4}}/{slug:[a-z-]+}", 5);
+
+        assert_eq!(
+            lookup(&router, "/users/42"),
+            Some((1, vec![param("id", "42")]))
+        );
+        assert_eq!(
+            router
+                .get("/users/67e55044-10b1-426f-9247-bb680e5fe0c8")
+                .unwrap()
+                .value,
+            &2
+        );
+        assert_eq!(
+            lookup(&router, "/users/-1"),
+            Some((3, vec![param("name", "-1")]))
+        );
+        assert_eq!(
+            lookup(&router, "/users/+42"),
+            Some((3, vec![param("name", "+42")]))
+        );
+        assert_eq!(
+            lookup(&router, "/users/67e5504410b1426f9247bb680e5fe0c8"),
+            Some((3, vec![param("name", "67e5504410b1426f9247bb680e5fe0c8")]))
+        );
+        assert_eq!(
+            lookup(&router, "/posts/hello-world"),
+            Some((4, vec![param("slug", "hello-world")]))
+        );
+        assert_eq!(lookup(&router, "/posts/Hello"), None);
+        assert_eq!(
+            lookup(&router, "/posts/2024/hello"),
+            Some((5, vec![param("year", "2024"), param("slug", "hello")]))
+        );
+        assert_eq!(lookup(&router, "/posts/24/hello"), None);
+
+        assert_eq!(router.insert("/users/{user:u64}", 6), Some(1));
+        assert_eq!(
+            router.get_mut(r"/posts/{y:\d{4}}/{s:[a-z-]+}"),
+            Some(&mut 5)
+        );
+        assert_eq!(router.len(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_segments_after_catch_all() {
+        Router::new().insert("/files/*path/meta", 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_invalid_constraints() {
+        Router::new().insert("/users/{id:[0-9}", 1);
+    }
+}